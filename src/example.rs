@@ -0,0 +1,224 @@
+use std::fmt::Write;
+
+use crate::model;
+
+
+/// Placeholder used for text and attribute values without any hints.
+const ELLIPSIS: &str = "...";
+
+/// Indentation of nested elements.
+const INDENT: &str = "  ";
+
+/// Nesting depth of synthesized examples unless configured otherwise.
+pub const DEFAULT_DEPTH: usize = 4;
+
+
+/// Shape of an example produced by [`synthesize`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExampleKind {
+    /// Only required attributes and children.
+    Minimal,
+    /// Every known attribute and child.
+    Full,
+}
+
+
+/// Synthesize an XML example of `tag` by walking its attributes and resolved children.
+/// Nesting stops at `max_depth` levels (or on recursion), leaving an XML comment instead,
+/// so `max_depth` must be at least 1.
+pub fn synthesize(root: &model::TagList, tag: &model::Tag, kind: ExampleKind, max_depth: usize) -> String {
+    debug_assert!(max_depth > 0);
+    let mut synthesizer = Synthesizer {
+        root,
+        kind,
+        max_depth,
+        ancestors: vec![],
+        output: String::new(),
+    };
+
+    synthesizer.element(tag, 0);
+    synthesizer.output
+}
+
+
+struct Synthesizer<'a> {
+    root: &'a model::TagList,
+    kind: ExampleKind,
+    max_depth: usize,
+    ancestors: Vec<model::TagId>,
+    output: String,
+}
+
+impl<'a> Synthesizer<'a> {
+    fn element(&mut self, tag: &'a model::Tag, depth: usize) {
+        let indent = INDENT.repeat(depth);
+        let name = format!("{}:{}", self.root.namespace, tag.name);
+
+        // Writing into a String never fails.
+        let _ = write!(self.output, "{}<{}", indent, name);
+        // Global attributes would only repeat themselves on every element.
+        let attributes = tag.attributes.iter()
+            .filter(|a| a.origin != model::AttributeOrigin::Global)
+            .filter(|a| self.kind == ExampleKind::Full || !a.is_optional);
+        for attr in attributes {
+            let _ = write!(self.output, " {}=\"{}\"", attr.name, escape(&attribute_value(attr)));
+        }
+
+        let children = tag.children.iter()
+            .filter(|c| self.kind == ExampleKind::Full || !c.is_optional)
+            .filter_map(|c| match &c.reference {
+                model::ChildInternal::Resolved { id } => self.root.get(*id),
+                model::ChildInternal::Abstract { .. } | model::ChildInternal::Unresolved { .. } => None,
+            })
+            .collect::<Vec<_>>();
+
+        if children.is_empty() && tag.value.is_none() {
+            let _ = writeln!(self.output, "/>");
+            return;
+        }
+
+        if children.is_empty() {
+            let _ = writeln!(self.output, ">{}</{}>", ELLIPSIS, name);
+            return;
+        }
+
+        let _ = writeln!(self.output, ">");
+        let stop = depth + 1 >= self.max_depth || self.ancestors.contains(&tag.id);
+        if stop {
+            let _ = writeln!(self.output, "{}{}<!-- ... -->", indent, INDENT);
+        } else {
+            self.ancestors.push(tag.id);
+            if tag.value.is_some() {
+                let _ = writeln!(self.output, "{}{}{}", indent, INDENT, ELLIPSIS);
+            }
+            for child in children {
+                self.element(child, depth + 1);
+            }
+            self.ancestors.pop();
+        }
+        let _ = writeln!(self.output, "{}</{}>", indent, name);
+    }
+}
+
+/// Pick an attribute value: its default, else the first `quoted` token of the expected value hint.
+fn attribute_value(attr: &model::Attribute) -> String {
+    if let Some(default) = &attr.default_value {
+        return default.to_string();
+    }
+
+    match &attr.expected_value {
+        Some(expected) => match expected.split('`').nth(1) {
+            Some(token) if !token.is_empty() => token.into(),
+            _ => expected.to_string(),
+        },
+        None => ELLIPSIS.into(),
+    }
+}
+
+/// Escape text for use within a double-quoted XML attribute value.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::loader;
+    use crate::schema::SourceFormat;
+
+    fn load(text: &str) -> model::TagList {
+        let (root, _) = SourceFormat::Yaml.parse(text).unwrap();
+        loader::load_from(root).unwrap().model
+    }
+
+    fn example_of(root: &model::TagList, name: &str, kind: ExampleKind, max_depth: usize) -> String {
+        let tag = root.iter().find(|tag| tag.name == name).unwrap();
+        synthesize(root, tag, kind, max_depth)
+    }
+
+    #[test]
+    fn minimal_examples_skip_optional_parts() {
+        let root = load("
+schema: { version: r1, namespace: ex }
+tags:
+  - id: list
+    description: List.
+    attributes:
+      - { id: id, brief: Identifier. }
+      - { id: style, brief: Style., optional: true }
+    children: [{ ref: item }, { ref: note, optional: true }]
+  - { id: item, description: Item., value: Text. }
+  - { id: note, description: Note. }
+");
+        assert_eq!(example_of(&root, "list", ExampleKind::Minimal, DEFAULT_DEPTH), "\
+<ex:list id=\"...\">
+  <ex:item>...</ex:item>
+</ex:list>
+");
+        assert_eq!(example_of(&root, "list", ExampleKind::Full, DEFAULT_DEPTH), "\
+<ex:list id=\"...\" style=\"...\">
+  <ex:item>...</ex:item>
+  <ex:note/>
+</ex:list>
+");
+    }
+
+    #[test]
+    fn nesting_stops_on_recursion() {
+        let root = load("
+schema: { version: r1, namespace: ex }
+tags:
+  - { id: group, description: Group., children: [{ ref: group, optional: true }] }
+");
+        assert_eq!(example_of(&root, "group", ExampleKind::Full, DEFAULT_DEPTH), "\
+<ex:group>
+  <ex:group>
+    <!-- ... -->
+  </ex:group>
+</ex:group>
+");
+    }
+
+    #[test]
+    fn nesting_stops_at_max_depth() {
+        let root = load("
+schema: { version: r1, namespace: ex }
+tags:
+  - { id: a, description: A., children: [{ ref: b }] }
+  - { id: b, description: B., children: [{ ref: c }] }
+  - { id: c, description: C. }
+");
+        assert_eq!(example_of(&root, "a", ExampleKind::Minimal, 2), "\
+<ex:a>
+  <ex:b>
+    <!-- ... -->
+  </ex:b>
+</ex:a>
+");
+        assert_eq!(example_of(&root, "a", ExampleKind::Minimal, 1), "\
+<ex:a>
+  <!-- ... -->
+</ex:a>
+");
+    }
+
+    #[test]
+    fn attribute_values_prefer_defaults_then_quoted_hints() {
+        let root = load(r#"
+schema: { version: r1, namespace: ex }
+tags:
+  - id: item
+    description: Item.
+    attributes:
+      - { id: a, brief: A., expected: "`yes` or `no`", default: "no", optional: true }
+      - { id: b, brief: B., expected: "`yes` or `no`" }
+      - { id: c, brief: C., expected: "a number" }
+      - { id: d, brief: D., expected: "``" }
+      - { id: e, brief: E., default: "<\"&\">", optional: true }
+"#);
+        assert_eq!(example_of(&root, "item", ExampleKind::Full, DEFAULT_DEPTH),
+            "<ex:item a=\"no\" b=\"yes\" c=\"a number\" d=\"``\" e=\"&lt;&quot;&amp;&quot;>\"/>\n");
+    }
+}
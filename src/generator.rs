@@ -1,8 +1,26 @@
-use std::cell::RefCell;
+mod anchors;
+mod html;
+pub mod index;
+pub mod introduction;
+pub mod json;
+mod markdown;
+pub mod template;
+
+pub use anchors::{Anchors, AnchorStyle};
+pub use html::HtmlRenderer;
+pub use index::Index;
+pub use introduction::Introduction;
+pub use markdown::MarkdownRenderer;
+pub use template::TemplateRenderer;
+
+use std::collections::HashSet;
 use std::io;
 use std::fmt;
-use smallvec::SmallVec;
+use std::path::PathBuf;
+use std::str::FromStr;
 
+use super::example;
+use super::graph;
 use super::model;
 
 
@@ -19,6 +37,10 @@ pub enum GeneratorError {
     InternalFormatting { inner: fmt::Error, description: Option<String> },
     /// Generator suffered an input/output error.
     InternalInputOutput { inner: io::Error, description: Option<String> },
+    /// A user-supplied (or built-in) template failed to compile.
+    BadTemplate { name: String, inner: Box<handlebars::TemplateError> },
+    /// Generator suffered a template rendering error.
+    InternalTemplate { inner: Box<handlebars::RenderError>, description: Option<String> },
 }
 
 impl fmt::Display for GeneratorError {
@@ -40,6 +62,27 @@ impl fmt::Display for GeneratorError {
                     None => Ok(()),
                 }
             }
+            GeneratorError::BadTemplate { name, inner } =>
+                f.write_fmt(format_args!("invalid template '{}': {}", name, inner)),
+            GeneratorError::InternalTemplate { inner, description } => {
+                f.write_fmt(format_args!("internal template error: {}", inner))?;
+                match description {
+                    Some(desc) => f.write_fmt(format_args!(", description: {}", desc)),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+impl std::error::Error for GeneratorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GeneratorError::BadHeaderLevel { .. } => None,
+            GeneratorError::InternalFormatting { inner, .. } => Some(inner),
+            GeneratorError::InternalInputOutput { inner, .. } => Some(inner),
+            GeneratorError::BadTemplate { inner, .. } => Some(inner.as_ref()),
+            GeneratorError::InternalTemplate { inner, .. } => Some(inner.as_ref()),
         }
     }
 }
@@ -57,13 +100,180 @@ impl From<io::Error> for GeneratorError {
 }
 
 
-/// Configuration struct passed to Markdown generator functions.
+/// Configuration struct passed to generator functions.
 #[derive(Debug)]
 pub struct GeneratorOptions {
+    /// The kind of document to produce.
+    pub format: OutputFormat,
     /// The starting heading level that the generator should descend from.
     pub level: HeaderLevel,
     /// Whether to use CRLF for new lines instead of LF.
     pub crlf: bool,
+    /// Whether to synthesize examples for tags which don't have one.
+    pub synthesize_examples: bool,
+    /// How many levels of nested tags a synthesized example may contain.
+    pub example_depth: usize,
+    /// How tag headings get their link targets.
+    pub anchor_style: AnchorStyle,
+    /// How to lay out attributes, overriding the preference of the tag list.
+    pub attribute_layout: Option<AttributeLayout>,
+    /// Directory with `.hbs` files overriding the built-in [`OutputFormat::Template`] templates.
+    pub templates: Option<PathBuf>,
+    /// Whether to start the document with an [`Index`] of all tags.
+    pub index: bool,
+    /// Hierarchy graph to embed at the start of the document, if any.
+    pub graph: Option<graph::GraphFormat>,
+    /// Order in which tags are described.
+    pub order: TagOrder,
+    /// Whether to describe abstract base tags alongside the tags extending them.
+    pub abstract_tags: bool,
+}
+
+
+/// Document formats supported by [`generate`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Opinionated Markdown, suitable for any mdBook renderer.
+    #[default]
+    Markdown,
+    /// Semantic HTML fragment with `xmldoc-*` classes, for the mdBook html renderer.
+    Html,
+    /// Handlebars templates rendered against a [`template::TagView`] of every tag.
+    Template,
+    /// Versioned JSON dump of the loaded model, see [`json::ModelView`].
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "markdown" | "md" => Ok(OutputFormat::Markdown),
+            "html" => Ok(OutputFormat::Html),
+            "template" | "handlebars" => Ok(OutputFormat::Template),
+            "json" => Ok(OutputFormat::Json),
+            other => Err(format!("unknown output format '{}', expected 'markdown', 'html', 'template' or 'json'", other)),
+        }
+    }
+}
+
+impl GeneratorOptions {
+    /// Get the tags of `root` to describe, in the configured order.
+    pub fn arrange<'a>(&self, root: &'a model::TagList) -> Vec<&'a model::Tag> {
+        let mut tags = self.order.arrange(root);
+        if !self.abstract_tags {
+            tags.retain(|tag| !tag.is_abstract);
+        }
+        tags
+    }
+
+    /// Resolve the attribute layout for `root`: explicit option, then tag list preference, then list.
+    pub fn attribute_layout_for(&self, root: &model::TagList) -> AttributeLayout {
+        self.attribute_layout.or(root.attribute_layout).unwrap_or_default()
+    }
+}
+
+
+/// Ways of laying out the attributes section.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AttributeLayout {
+    /// Nested bullet list per attribute.
+    #[default]
+    List,
+    /// Table with long descriptions folded into the description cell.
+    Table,
+    /// Table with long descriptions placed as footnotes below it.
+    Footnotes,
+}
+
+impl FromStr for AttributeLayout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "list" => Ok(AttributeLayout::List),
+            "table" => Ok(AttributeLayout::Table),
+            "footnotes" | "table-footnotes" => Ok(AttributeLayout::Footnotes),
+            other => Err(format!("unknown attribute layout '{}', expected 'list', 'table' or 'footnotes'", other)),
+        }
+    }
+}
+
+impl AttributeLayout {
+    /// Get the canonical name of this layout, as accepted by [`AttributeLayout::from_str`].
+    pub fn name(&self) -> &'static str {
+        match self {
+            AttributeLayout::List => "list",
+            AttributeLayout::Table => "table",
+            AttributeLayout::Footnotes => "footnotes",
+        }
+    }
+}
+
+
+/// Orders in which tags can be described.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TagOrder {
+    /// As defined in the tag list.
+    #[default]
+    Source,
+    /// By tag name, ignoring case.
+    Alphabetical,
+    /// Depth-first from the root tags, then any tags unreachable from them in source order.
+    Hierarchy,
+}
+
+impl FromStr for TagOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "source" => Ok(TagOrder::Source),
+            "alphabetical" | "name" => Ok(TagOrder::Alphabetical),
+            "hierarchy" | "tree" => Ok(TagOrder::Hierarchy),
+            other => Err(format!("unknown tag order '{}', expected 'source', 'alphabetical' or 'hierarchy'", other)),
+        }
+    }
+}
+
+impl TagOrder {
+    /// Arrange all tags of `root` in this order.
+    pub fn arrange<'a>(&self, root: &'a model::TagList) -> Vec<&'a model::Tag> {
+        match self {
+            TagOrder::Source => root.iter().collect(),
+            TagOrder::Alphabetical => {
+                let mut tags = root.iter().collect::<Vec<_>>();
+                tags.sort_by(|a, b| {
+                    a.name.to_lowercase().cmp(&b.name.to_lowercase()).then_with(|| a.name.cmp(&b.name))
+                });
+                tags
+            },
+            TagOrder::Hierarchy => {
+                let mut visited = HashSet::with_capacity(root.tags.len());
+                let mut tags = Vec::with_capacity(root.tags.len());
+                for tag in root.roots.iter().map(|id| &root[*id]).chain(root.iter()) {
+                    visit_depth_first(root, tag, &mut visited, &mut tags);
+                }
+                tags
+            },
+        }
+    }
+}
+
+fn visit_depth_first<'a>(root: &'a model::TagList, tag: &'a model::Tag,
+                         visited: &mut HashSet<model::TagId>, tags: &mut Vec<&'a model::Tag>)
+{
+    if !visited.insert(tag.id) {
+        return;
+    }
+
+    tags.push(tag);
+    for child in &tag.children {
+        if let model::ChildInternal::Resolved { id } = &child.reference {
+            visit_depth_first(root, &root[*id], visited, tags);
+        }
+    }
 }
 
 
@@ -99,6 +309,12 @@ impl HeaderLevel {
             _ => panic!("invalid internal header level"),
         }
     }
+
+    /// Get the numeric value of this heading level.
+    #[inline]
+    pub fn get(&self) -> i32 {
+        self.0
+    }
 }
 
 impl fmt::Display for HeaderLevel {
@@ -108,68 +324,194 @@ impl fmt::Display for HeaderLevel {
 }
 
 
-/// Generate Markdown content into `formatter` from the `root` tag list using the given `options`.
+/// Generate content into `formatter` from the `root` tag list using the given `options`.
 pub fn generate<'a>(root: &'a model::TagList, options: &'a GeneratorOptions,
                     formatter: &'a mut dyn io::Write) -> GeneratorResult<()>
 {
-    let context = Context {
-        options,
-        writer: RefCell::new(formatter),
-        newline: if options.crlf { "\r\n" } else { "\n" },
-        newblock: if options.crlf { "\r\n\r\n" } else { "\n\n" },
-    };
-
-    // Instead of preserving order on model construction, it is recovered here.
-    // To consider: move this to model, or rework the model to preserve the order inherently.
-    let ordered_tags = {
-        type TagPair<'a> = (&'a uuid::Uuid, &'a model::Tag);
-        let mut pairs: Vec<TagPair> = root.tags.iter().collect();
-        pairs.sort_by(|a: &TagPair, b: &TagPair| {
-            a.1.index().partial_cmp(&b.1.index()).unwrap()
-        });
-        pairs
-    };
-
-    for (uuid, tag) in ordered_tags {
-        context.writer_tag_header(&root.namespace, &tag.name)?;
-        context.write_paragraph(&tag.description)?;
-
-        if !tag.attributes.is_empty() {
-            context.write_tag_subheader("Attributes")?;
-            for attr in &tag.attributes {
-                context.write_attribute(
-                    &attr.name,
-                    &attr.short_description,
-                    attr.long_description.as_deref(),
-                    attr.is_optional,
-                    attr.expected_value.as_deref(),
-                    attr.default_value.as_deref(),
-                )?;
+    match options.format {
+        OutputFormat::Markdown => generate_with(root, options, &mut MarkdownRenderer::new(options, formatter)),
+        OutputFormat::Html => generate_with(root, options, &mut HtmlRenderer::new(options, formatter)),
+        OutputFormat::Template => {
+            let mut renderer = TemplateRenderer::new(options, formatter, options.templates.as_deref())?;
+            generate_with(root, options, &mut renderer)
+        },
+        OutputFormat::Json => json::write(root, options, formatter),
+    }
+}
+
+
+/// Parts of a tag description, in the order they are produced.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Section {
+    /// List of [`Renderer::attribute`] items.
+    Attributes,
+    /// A single [`Renderer::value`] description.
+    Value,
+    /// List of [`Renderer::child`] items.
+    Children,
+    /// List of [`Renderer::parent`] items, or a single [`Renderer::no_parents`] call.
+    Parents,
+    /// A single [`Renderer::example`], which may be repeated with a different title.
+    Example,
+}
+
+/// Output-specific half of the generator, driven by [`generate_with`].
+///
+/// Tags are visited as arranged by [`GeneratorOptions::arrange`]: in [`GeneratorOptions::order`],
+/// skipping abstract tags unless [`GeneratorOptions::abstract_tags`] is set. For every tag,
+/// [`Renderer::tag_begin`] is followed by its non-empty sections, each enclosed in
+/// [`Renderer::section_begin`] and [`Renderer::section_end`], and finally by [`Renderer::tag_end`]. The whole run is enclosed in
+/// [`Renderer::document_begin`] and [`Renderer::document_end`], with [`Renderer::introduction`],
+/// [`Renderer::index`], [`Renderer::graph`] and [`Renderer::global_attributes`] called right after
+/// the former if applicable.
+pub trait Renderer {
+    /// Called once before any tag is rendered.
+    fn document_begin(&mut self, _root: &model::TagList) -> GeneratorResult<()> {
+        Ok(())
+    }
+    /// Called once after all tags have been rendered.
+    fn document_end(&mut self, _root: &model::TagList) -> GeneratorResult<()> {
+        Ok(())
+    }
+    /// Render the document-level introduction, if the tag list has one.
+    fn introduction(&mut self, _introduction: &Introduction) -> GeneratorResult<()> {
+        Ok(())
+    }
+    /// Render the index of all tags, if [`GeneratorOptions::index`] is set.
+    fn index(&mut self, _index: &Index) -> GeneratorResult<()> {
+        Ok(())
+    }
+    /// Embed the hierarchy graph `code`, if [`GeneratorOptions::graph`] is set.
+    fn graph(&mut self, _format: graph::GraphFormat, _code: &str) -> GeneratorResult<()> {
+        Ok(())
+    }
+    /// Render the attributes every tag accepts under a heading with the given `anchor`,
+    /// if the tag list has any.
+    fn global_attributes(&mut self, _anchor: &str, _attributes: &[model::Attribute]) -> GeneratorResult<()> {
+        Ok(())
+    }
+    /// Refer to the global attributes at `link` from the current tag, right after its attributes section.
+    fn global_attributes_link(&mut self, _link: &str) -> GeneratorResult<()> {
+        Ok(())
+    }
+
+    /// Start the description of `tag`, including its heading (with the unique `anchor`)
+    /// and description text.
+    fn tag_begin(&mut self, anchor: &str, namespace: &str, tag: &model::Tag) -> GeneratorResult<()>;
+    /// Finish the description of `tag`.
+    fn tag_end(&mut self, _tag: &model::Tag) -> GeneratorResult<()> {
+        Ok(())
+    }
+
+    /// Start a section of the current tag with a human-readable `title`.
+    fn section_begin(&mut self, section: Section, title: &str) -> GeneratorResult<()>;
+    /// Finish the current section.
+    fn section_end(&mut self, _section: Section) -> GeneratorResult<()> {
+        Ok(())
+    }
+
+    /// Render an attribute item, declared as told by `source`.
+    fn attribute(&mut self, attr: &model::Attribute, source: AttributeSource<'_>) -> GeneratorResult<()>;
+    /// Render the scalar value description.
+    fn value(&mut self, text: &str) -> GeneratorResult<()>;
+    /// Render a child item, which only has a `link` target if its reference is resolved.
+    fn child(&mut self, link: Option<&str>, namespace: &str, name: &str, optional: bool, repeated: bool) -> GeneratorResult<()>;
+    /// Render a parent item linking to `link`.
+    fn parent(&mut self, link: &str, namespace: &str, name: &str) -> GeneratorResult<()>;
+    /// Render a note that the tag has no possible parents.
+    fn no_parents(&mut self) -> GeneratorResult<()>;
+    /// Render example XML code.
+    fn example(&mut self, code: &str) -> GeneratorResult<()>;
+}
+
+/// Where an attribute passed to [`Renderer::attribute`] was declared.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttributeSource<'a> {
+    /// By the described tag itself.
+    Declared,
+    /// By a base tag, which only has a `link` target if it's described too.
+    Inherited { link: Option<&'a str>, namespace: &'a str, name: &'a str },
+}
+
+/// Link target of the section describing global attributes, see [`Renderer::global_attributes`].
+pub const GLOBAL_ATTRIBUTES_ANCHOR: &str = "global-attributes";
+
+/// Walk the `root` tag list, producing content through a custom `renderer`.
+pub fn generate_with(root: &model::TagList, options: &GeneratorOptions, renderer: &mut dyn Renderer) -> GeneratorResult<()> {
+    let ordered_tags = options.arrange(root);
+    let anchors = Anchors::new(&root.namespace, ordered_tags.iter().map(|tag| (tag.id, tag.name.as_str())));
+
+    renderer.document_begin(root)?;
+    if let Some(introduction) = Introduction::new(root, &anchors) {
+        renderer.introduction(&introduction)?;
+    }
+    if options.index {
+        renderer.index(&Index::new(root, &anchors))?;
+    }
+    if let Some(format) = options.graph {
+        renderer.graph(format, &graph::draw(root, format))?;
+    }
+    if !root.global_attributes.is_empty() {
+        renderer.global_attributes(GLOBAL_ATTRIBUTES_ANCHOR, &root.global_attributes)?;
+    }
+
+    for tag in ordered_tags {
+        renderer.tag_begin(anchors.get(tag.id), &root.namespace, tag)?;
+
+        // Global attributes are described once, tags only refer to them.
+        let attributes = tag.attributes.iter()
+            .filter(|attr| attr.origin != model::AttributeOrigin::Global)
+            .collect::<Vec<_>>();
+        if !attributes.is_empty() {
+            renderer.section_begin(Section::Attributes, "Attributes")?;
+            for attr in &attributes {
+                let source = match attr.origin {
+                    model::AttributeOrigin::Inherited { base } => AttributeSource::Inherited {
+                        link: anchors.find(base),
+                        namespace: &root.namespace,
+                        name: &root[base].name,
+                    },
+                    _ => AttributeSource::Declared,
+                };
+                renderer.attribute(attr, source)?;
             }
-            context.write_newblock()?;
+            renderer.section_end(Section::Attributes)?;
+        }
+        if attributes.len() < tag.attributes.len() {
+            renderer.global_attributes_link(GLOBAL_ATTRIBUTES_ANCHOR)?;
         }
 
         if let Some(value) = &tag.value {
-            context.write_tag_subheader("Value")?;
-            context.write_paragraph(value)?;
+            renderer.section_begin(Section::Value, "Value")?;
+            renderer.value(value)?;
+            renderer.section_end(Section::Value)?;
         }
 
         if !tag.children.is_empty() {
-            context.write_tag_subheader("Children")?;
+            renderer.section_begin(Section::Children, "Children")?;
             for child in &tag.children {
                 match &child.reference {
                     model::ChildInternal::Resolved { id } => {
-                        context.write_child_item(
-                            true,
+                        renderer.child(
+                            Some(anchors.get(*id)),
                             &root.namespace,
-                            &root.tags.get(id).unwrap().name,
+                            &root[*id].name,
+                            child.is_optional,
+                            child.is_repeatable,
+                        )?;
+                    },
+                    model::ChildInternal::Abstract { id } => {
+                        renderer.child(
+                            anchors.find(*id),
+                            &root.namespace,
+                            &root[*id].name,
                             child.is_optional,
                             child.is_repeatable,
                         )?;
                     },
                     model::ChildInternal::Unresolved { name } => {
-                        context.write_child_item(
-                            false,
+                        renderer.child(
+                            None,
                             &root.namespace,
                             name,
                             child.is_optional,
@@ -178,130 +520,123 @@ pub fn generate<'a>(root: &'a model::TagList, options: &'a GeneratorOptions,
                     },
                 };
             }
-            context.write_newblock()?;
+            renderer.section_end(Section::Children)?;
         }
 
         // Parent block is always present.
         {
-            context.write_tag_subheader("Parents")?;
-            match root.parents.get(uuid) {
+            renderer.section_begin(Section::Parents, "Parents")?;
+            match root.parents.get(&tag.id) {
                 Some(parents) => {
-                    'parents: for parent_uuid in parents {
-                        match root.tags.get(parent_uuid) {
+                    'parents: for parent_id in parents {
+                        match root.get(*parent_id) {
                             Some(parent_tag) => {
                                 let name = parent_tag.name.as_str();
-                                context.write_parent_item(&root.namespace, name)?;
+                                renderer.parent(anchors.get(*parent_id), &root.namespace, name)?;
                             }
                             None => {
-                                log::warn!("failed to resolve parent name for {} -> {}", uuid, parent_uuid);
+                                log::warn!("failed to resolve parent name for {} -> {}", tag.id, parent_id);
                                 continue 'parents;
                             }
                         };
                     }
-                    context.write_newblock()?;
                 }
-                None => context.write_paragraph("This tag has no possible parents!")?,
+                None => renderer.no_parents()?,
             }
+            renderer.section_end(Section::Parents)?;
         }
 
         if let Some(example) = &tag.example {
-            context.write_tag_subheader("Example")?;
-            context.write_xml(example)?;
+            renderer.section_begin(Section::Example, "Example")?;
+            renderer.example(example)?;
+            renderer.section_end(Section::Example)?;
+        } else if options.synthesize_examples {
+            let minimal = example::synthesize(root, tag, example::ExampleKind::Minimal, options.example_depth);
+            let full = example::synthesize(root, tag, example::ExampleKind::Full, options.example_depth);
+
+            renderer.section_begin(Section::Example, "Example (generated)")?;
+            renderer.example(&minimal)?;
+            renderer.section_end(Section::Example)?;
+            if full != minimal {
+                renderer.section_begin(Section::Example, "Full example (generated)")?;
+                renderer.example(&full)?;
+                renderer.section_end(Section::Example)?;
+            }
         }
-    }
-
-    Ok(())
-}
-
-struct Context<'a> {
-    options: &'a GeneratorOptions,
-    writer: RefCell<&'a mut dyn io::Write>,
-    newline: &'static str,
-    newblock: &'static str,
-}
-
-//noinspection RsBorrowChecker  - clion why
-impl<'a> Context<'a> {
-    pub fn writer_tag_header(&self, namespace: &str, title: &str) -> GeneratorResult<()> {
-        let mut writer = self.writer.borrow_mut();
-        write!(writer, "{} `{}:{}`{}", self.options.level.get_prefix(), namespace, title, self.newblock)?;
-        Ok(())
-    }
-
-    pub fn write_tag_subheader(&self, text: &str) -> GeneratorResult<()> {
-        let mut writer = self.writer.borrow_mut();
-        write!(writer, "_**{}:**_{}", text, self.newblock)?;
-        Ok(())
-    }
 
-    pub fn write_paragraph(&self, text: &str) -> GeneratorResult<()> {
-        let mut writer = self.writer.borrow_mut();
-        write!(writer, "{}{}", text, self.newblock)?;
-        Ok(())
+        renderer.tag_end(tag)?;
     }
 
-    pub fn write_attribute(&self,
-                           name: &str,
-                           brief: &str,
-                           desc: Option<&str>,
-                           optional: bool,
-                           expected: Option<&str>,
-                           r#default: Option<&str>) -> GeneratorResult<()>
-    {
-        let mut writer = self.writer.borrow_mut();
-
-        let optional_text = if optional { " _(optional)_" } else { "" };
-        write!(writer, "* `{}` - {}{}{}", name, brief, optional_text, self.newline)?;
-
-        if let Some(desc) = desc {
-            write!(writer, "  * {}{}", desc, self.newline)?;
-        }
-
-        if let Some(expected) = expected {
-            write!(writer, "  * _Expected value:_ {}{}", expected, self.newline)?;
-        }
+    renderer.document_end(root)
+}
 
-        if let Some(r#default) = r#default {
-            write!(writer, "  * _Default value:_ {}{}", r#default, self.newline)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::loader;
+    use crate::schema::SourceFormat;
+
+    pub(crate) fn options(format: OutputFormat) -> GeneratorOptions {
+        GeneratorOptions {
+            format,
+            level: HeaderLevel::new(1).unwrap(),
+            crlf: false,
+            synthesize_examples: false,
+            example_depth: crate::example::DEFAULT_DEPTH,
+            anchor_style: AnchorStyle::default(),
+            attribute_layout: None,
+            templates: None,
+            index: false,
+            graph: None,
+            order: TagOrder::default(),
+            abstract_tags: false,
         }
-
-        Ok(())
     }
 
-    pub fn write_parent_item(&self, namespace: &str, name: &str) -> GeneratorResult<()> {
-        let mut writer = self.writer.borrow_mut();
-        write!(writer, "* [`{}:{}`](#{}{}){}", namespace, name, &namespace.to_lowercase(), &name.to_lowercase(), self.newline)?;
-        Ok(())
+    pub(crate) fn load(text: &str) -> model::TagList {
+        let (root, _) = SourceFormat::Yaml.parse(text).unwrap();
+        loader::load_from(root).unwrap().model
     }
 
-    pub fn write_child_item(&self, linked: bool, namespace: &str, name: &str, optional: bool, repeated: bool) -> GeneratorResult<()> {
-        let mut writer = self.writer.borrow_mut();
-        if linked {
-            write!(writer, "* [`{}:{}`](#{}{})", namespace, name, &namespace.to_lowercase(), &name.to_lowercase())?;
-        } else {
-            write!(writer, "* `{}:{}`", namespace, name)?;
-        }
-
-        if optional || repeated {
-            let mut modifiers = SmallVec::<[&'static str; 2]>::new();
-            if optional { modifiers.push("optional"); }
-            if repeated { modifiers.push("repeated"); }
-            write!(writer, " _({})_", modifiers.join(", "))?;
-        }
-
-        write!(writer, "{}", self.newline)?;
-        Ok(())
+    pub(crate) fn render(root: &model::TagList, options: &GeneratorOptions) -> String {
+        let mut output = Vec::new();
+        generate(root, options, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
     }
 
-    pub fn write_xml(&self, code: &str) -> GeneratorResult<()> {
-        let mut writer = self.writer.borrow_mut();
-        write!(writer, "```xml{}{}{}```{}", self.newline, code.trim_end(), self.newline, self.newblock)?;
-        Ok(())
+    #[test]
+    fn hierarchy_order_is_depth_first_from_roots_then_source_order() {
+        let root = load("
+schema: { version: r1, namespace: ex, roots: [doc] }
+tags:
+  - { id: orphan, description: Orphan. }
+  - { id: para, description: Para., children: [{ ref: section, optional: true }] }
+  - { id: meta, description: Meta. }
+  - { id: section, description: Section., children: [{ ref: para }, { ref: section, optional: true }] }
+  - { id: doc, description: Doc., children: [{ ref: section }, { ref: meta }] }
+  - { id: x, description: X., children: [{ ref: y, optional: true }] }
+  - { id: y, description: Y., children: [{ ref: x, optional: true }] }
+");
+        let names = |order: TagOrder| order.arrange(&root).iter().map(|tag| tag.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names(TagOrder::Hierarchy), ["doc", "section", "para", "meta", "orphan", "x", "y"]);
+        assert_eq!(names(TagOrder::Source), ["orphan", "para", "meta", "section", "doc", "x", "y"]);
+        assert_eq!(names(TagOrder::Alphabetical), ["doc", "meta", "orphan", "para", "section", "x", "y"]);
     }
 
-    pub fn write_newblock(&self) -> GeneratorResult<()> {
-        let mut writer = self.writer.borrow_mut();
-        write!(writer, "{}", self.newblock)?;
-        Ok(())
+    #[test]
+    fn global_attributes_are_described_once() {
+        let root = load("
+schema: { version: r1, namespace: ex }
+global_attributes: [{ id: lang, brief: Language of the content. }]
+tags:
+  - { id: list, description: List., children: [{ ref: item }] }
+  - { id: item, description: Item. }
+");
+        let output = render(&root, &options(OutputFormat::Markdown));
+
+        assert_eq!(output.matches("# Global attributes").count(), 1);
+        assert_eq!(output.matches("Language of the content.").count(), 1);
+        assert_eq!(output.matches("[global attributes](#global-attributes)").count(), 2);
+        assert!(output.find("# Global attributes") < output.find("ex:list"));
     }
 }
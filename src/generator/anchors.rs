@@ -0,0 +1,189 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::str::FromStr;
+
+use crate::model::TagId;
+use super::GLOBAL_ATTRIBUTES_ANCHOR;
+
+
+/// Ids of the fixed headings a document may start with, which tags can't take.
+const RESERVED_IDS: [&str; 3] = ["introduction", "index", GLOBAL_ATTRIBUTES_ANCHOR];
+
+
+/// How tag headings get their link targets.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AnchorStyle {
+    /// Rely on the ids mdBook derives from heading text.
+    #[default]
+    Implicit,
+    /// Write explicit `{#id}` heading attributes.
+    Attribute,
+    /// Write an explicit `<a id="...">` element before every heading.
+    Html,
+}
+
+impl FromStr for AnchorStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "implicit" => Ok(AnchorStyle::Implicit),
+            "attribute" => Ok(AnchorStyle::Attribute),
+            "html" => Ok(AnchorStyle::Html),
+            other => Err(format!("unknown anchor style '{}', expected 'implicit', 'attribute' or 'html'", other)),
+        }
+    }
+}
+
+impl AnchorStyle {
+    /// Get the style to write the heading of a tag with: implicit ids can't express
+    /// an `anchor` mdBook wouldn't derive on its own, so those are written as attributes.
+    pub fn for_anchor(self, anchor: &str, namespace: &str, name: &str) -> Self {
+        match self {
+            AnchorStyle::Implicit if anchor != Anchors::base(namespace, name) => AnchorStyle::Attribute,
+            style => style,
+        }
+    }
+}
+
+
+/// Unique link targets of all tag sections within a single generated document.
+#[derive(Debug, Default)]
+pub struct Anchors {
+    ids: HashMap<TagId, String>,
+}
+
+impl Anchors {
+    /// Assign ids to `(id, name)` tag pairs given in document order, as mdBook derives them from headings.
+    ///
+    /// Tags whose ids collide are numbered (`item`, `item-1`, `item-2`, ...) in source order
+    /// rather than document order, so every tag keeps its anchor however the document is arranged.
+    /// Ids of the fixed headings (`introduction`, `index` and `global-attributes`) are numbered the same way.
+    pub fn new<'a>(namespace: &str, tags: impl IntoIterator<Item = (TagId, &'a str)>) -> Self {
+        let mut groups = BTreeMap::<String, Vec<TagId>>::new();
+        for (id, name) in tags {
+            groups.entry(Anchors::base(namespace, name)).or_default().push(id);
+        }
+
+        let mut taken = groups.keys().cloned()
+            .chain(RESERVED_IDS.map(String::from))
+            .collect::<HashSet<_>>();
+        let mut ids = HashMap::new();
+        for (base, mut group) in groups {
+            group.sort();
+            let mut number = 0;
+            for (index, id) in group.into_iter().enumerate() {
+                if index == 0 && !RESERVED_IDS.contains(&base.as_str()) {
+                    ids.insert(id, base.clone());
+                    continue;
+                }
+                let anchor = loop {
+                    number += 1;
+                    let candidate = format!("{}-{}", base, number);
+                    if taken.insert(candidate.clone()) {
+                        break candidate;
+                    }
+                };
+                ids.insert(id, anchor);
+            }
+        }
+        Anchors { ids }
+    }
+
+    /// Get the id mdBook derives from the heading of a tag on its own, before any numbering.
+    pub fn base(namespace: &str, name: &str) -> String {
+        mdbook::utils::unique_id_from_content(&heading_html(namespace, name), &mut HashMap::new())
+    }
+
+    /// Get the link target of a tag, if it was passed to [`Anchors::new`].
+    pub fn find(&self, id: TagId) -> Option<&str> {
+        self.ids.get(&id).map(String::as_str)
+    }
+
+    /// Get the link target of a tag, which must have been passed to [`Anchors::new`].
+    pub fn get(&self, id: TagId) -> &str {
+        self.ids.get(&id).map(String::as_str).expect("anchor requested for an unknown tag")
+    }
+}
+
+/// Reproduce the HTML mdBook renders from a `` `namespace:name` `` heading.
+fn heading_html(namespace: &str, name: &str) -> String {
+    let text = format!("{}:{}", namespace, name)
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;");
+    format!("<code>{}</code>", text)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Ids mdBook assigns to the headings of `names`, when they appear in this order within a chapter.
+    fn mdbook_ids(namespace: &str, names: &[&str]) -> Vec<String> {
+        let mut counters = HashMap::new();
+        names.iter()
+            .map(|name| mdbook::utils::unique_id_from_content(&heading_html(namespace, name), &mut counters))
+            .collect()
+    }
+
+    fn anchors_of(namespace: &str, names: &[&str]) -> Vec<String> {
+        let anchors = Anchors::new(namespace, names.iter().enumerate().map(|(index, name)| (TagId::from_index(index), *name)));
+        (0..names.len()).map(|index| anchors.get(TagId::from_index(index)).to_string()).collect()
+    }
+
+    #[test]
+    fn base_matches_mdbook() {
+        for name in ["item", "Item", "list-item", "list_item", "item.v2", "ünïcode", "a-b-c"] {
+            assert_eq!(Anchors::base("ns", name), mdbook_ids("ns", &[name])[0], "name {:?}", name);
+        }
+        // The colon between namespace and name is dropped, like any other punctuation but dashes and underscores.
+        assert_eq!(Anchors::base("my-ns", "item"), mdbook_ids("my-ns", &["item"])[0]);
+        assert_eq!(Anchors::base("my-ns", "item"), "my-nsitem");
+        assert_eq!(Anchors::base("ns", "Item"), "nsitem");
+        assert_eq!(Anchors::base("ns", "list-item"), "nslist-item");
+    }
+
+    #[test]
+    fn colliding_ids_are_numbered_like_mdbook() {
+        let names = ["item", "Item", "ITEM", "other"];
+        assert_eq!(anchors_of("ns", &names), mdbook_ids("ns", &names));
+        assert_eq!(anchors_of("ns", &names), ["nsitem", "nsitem-1", "nsitem-2", "nsother"]);
+    }
+
+    #[test]
+    fn numbering_skips_taken_ids() {
+        // `item-1` already belongs to a tag of its own, so the second `item` gets the next number.
+        let anchors = anchors_of("ns", &["item", "item-1", "Item"]);
+        assert_eq!(anchors, ["nsitem", "nsitem-1", "nsitem-2"]);
+    }
+
+    #[test]
+    fn fixed_headings_keep_their_ids() {
+        let anchors = anchors_of("in", &["dex", "troduction", "other", "Dex"]);
+        assert_eq!(anchors, ["index-1", "introduction-1", "inother", "index-2"]);
+        let anchors = anchors_of("global", &["-attributes"]);
+        assert_eq!(anchors, ["global-attributes-1"]);
+        // mdBook numbers a tag heading after the `# Index` heading the same way.
+        let mut counters = HashMap::new();
+        mdbook::utils::unique_id_from_content("Index", &mut counters);
+        assert_eq!(mdbook::utils::unique_id_from_content(&heading_html("in", "dex"), &mut counters), "index-1");
+    }
+
+    #[test]
+    fn numbering_follows_source_order() {
+        let anchors = Anchors::new("ns", [(TagId::from_index(1), "Item"), (TagId::from_index(0), "item")]);
+        assert_eq!(anchors.get(TagId::from_index(0)), "nsitem");
+        assert_eq!(anchors.get(TagId::from_index(1)), "nsitem-1");
+        assert_eq!(anchors.find(TagId::from_index(2)), None);
+    }
+
+    #[test]
+    fn implicit_style_only_covers_derived_ids() {
+        assert_eq!(AnchorStyle::Implicit.for_anchor("nsitem", "ns", "item"), AnchorStyle::Implicit);
+        assert_eq!(AnchorStyle::Implicit.for_anchor("nsitem-1", "ns", "Item"), AnchorStyle::Attribute);
+        assert_eq!(AnchorStyle::Html.for_anchor("nsitem-1", "ns", "Item"), AnchorStyle::Html);
+        assert_eq!(AnchorStyle::Attribute.for_anchor("nsitem", "ns", "item"), AnchorStyle::Attribute);
+    }
+}
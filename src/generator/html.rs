@@ -0,0 +1,565 @@
+use std::io;
+use pulldown_cmark::{html, Options, Parser};
+
+use super::{AttributeLayout, AttributeSource, GeneratorOptions, GeneratorResult, Index, Introduction, Renderer, Section};
+use super::index::{IndexEntry, IndexNode};
+use crate::graph::GraphFormat;
+use crate::model;
+
+
+/// [`Renderer`] producing a semantic HTML fragment.
+///
+/// The output never contains blank lines, so that it stays a single raw HTML block
+/// when embedded into a Markdown chapter by the preprocessor.
+pub struct HtmlRenderer<'a> {
+    options: &'a GeneratorOptions,
+    writer: &'a mut dyn io::Write,
+    newline: &'static str,
+    /// Whether a `<ul>` was opened within the current section.
+    list_open: bool,
+    /// Attribute layout resolved for the current document.
+    layout: AttributeLayout,
+    /// Anchor of the current tag, used to make footnote ids unique.
+    tag_anchor: String,
+    /// Footnotes to write after the current attribute table, as `(id, html)` pairs.
+    footnotes: Vec<(String, String)>,
+}
+
+impl<'a> HtmlRenderer<'a> {
+    /// Create a renderer writing into `writer`.
+    pub fn new(options: &'a GeneratorOptions, writer: &'a mut dyn io::Write) -> Self {
+        HtmlRenderer {
+            options,
+            writer,
+            newline: if options.crlf { "\r\n" } else { "\n" },
+            list_open: false,
+            layout: options.attribute_layout.unwrap_or_default(),
+            tag_anchor: String::new(),
+            footnotes: vec![],
+        }
+    }
+
+    fn open_list(&mut self) -> GeneratorResult<()> {
+        if !self.list_open {
+            write!(self.writer, "<ul>{}", self.newline)?;
+            self.list_open = true;
+        }
+        Ok(())
+    }
+
+    fn write_markers(&mut self, markers: &[&str]) -> GeneratorResult<()> {
+        for marker in markers {
+            write!(self.writer, " <span class=\"xmldoc-marker xmldoc-{}\">{}</span>", marker, marker)?;
+        }
+        Ok(())
+    }
+
+    fn write_block(&mut self, text: &str) -> GeneratorResult<()> {
+        let rendered = render_markdown(text, self.newline);
+        write!(self.writer, "{}", rendered)?;
+        Ok(())
+    }
+
+    fn write_inherited(&mut self, source: AttributeSource<'_>) -> GeneratorResult<()> {
+        match source {
+            AttributeSource::Declared => (),
+            AttributeSource::Inherited { link: Some(link), namespace, name } =>
+                write!(self.writer, " <span class=\"xmldoc-inherited\">inherited from <a href=\"#{}\"><code>{}:{}</code></a></span>",
+                    link, escape(namespace), escape(name))?,
+            AttributeSource::Inherited { link: None, namespace, name } =>
+                write!(self.writer, " <span class=\"xmldoc-inherited\">inherited from <code>{}:{}</code></span>",
+                    escape(namespace), escape(name))?,
+        };
+        Ok(())
+    }
+
+    fn write_attribute_item(&mut self, attr: &model::Attribute, source: AttributeSource<'_>) -> GeneratorResult<()> {
+        self.open_list()?;
+
+        let requirement = if attr.is_optional { "xmldoc-optional" } else { "xmldoc-required" };
+        let deprecation = if attr.is_deprecated { " xmldoc-deprecated" } else { "" };
+        write!(self.writer, "<li class=\"xmldoc-attribute {}{}\">", requirement, deprecation)?;
+
+        let collapsible = attr.long_description.is_some()
+            || attr.expected_value.is_some()
+            || attr.default_value.is_some();
+        if collapsible {
+            write!(self.writer, "<details>{}<summary>", self.newline)?;
+        }
+
+        write!(self.writer, "<code>{}</code> - {}", escape(&attr.name), render_inline(&attr.short_description))?;
+        let mut markers = vec![];
+        if attr.is_optional { markers.push("optional"); }
+        if attr.is_deprecated { markers.push("deprecated"); }
+        self.write_markers(&markers)?;
+        self.write_inherited(source)?;
+
+        if collapsible {
+            write!(self.writer, "</summary>{}", self.newline)?;
+            if let Some(desc) = &attr.long_description {
+                self.write_block(desc)?;
+            }
+            if let Some(expected) = &attr.expected_value {
+                write!(self.writer, "<p class=\"xmldoc-expected\"><em>Expected value:</em> {}</p>{}",
+                    render_inline(expected), self.newline)?;
+            }
+            if let Some(r#default) = &attr.default_value {
+                write!(self.writer, "<p class=\"xmldoc-default\"><em>Default value:</em> {}</p>{}",
+                    render_inline(r#default), self.newline)?;
+            }
+            write!(self.writer, "</details>")?;
+        }
+
+        write!(self.writer, "</li>{}", self.newline)?;
+        Ok(())
+    }
+
+    fn write_attribute_row(&mut self, attr: &model::Attribute, source: AttributeSource<'_>) -> GeneratorResult<()> {
+        let requirement = if attr.is_optional { "xmldoc-optional" } else { "xmldoc-required" };
+        let deprecation = if attr.is_deprecated { " xmldoc-deprecated" } else { "" };
+        write!(self.writer, "<tr class=\"xmldoc-attribute {}{}\">", requirement, deprecation)?;
+
+        write!(self.writer, "<td><code>{}</code>", escape(&attr.name))?;
+        if attr.is_deprecated {
+            self.write_markers(&["deprecated"])?;
+        }
+        self.write_inherited(source)?;
+        write!(self.writer, "</td><td>{}</td><td>{}</td><td>{}</td><td>",
+            if attr.is_optional { "no" } else { "yes" },
+            attr.expected_value.as_deref().map(render_inline).unwrap_or_default(),
+            attr.default_value.as_deref().map(render_inline).unwrap_or_default())?;
+
+        match (&attr.long_description, self.layout) {
+            (Some(desc), AttributeLayout::Footnotes) => {
+                self.footnotes.push((format!("fn-{}-{}", self.tag_anchor, attr.name.to_lowercase()), render_markdown(desc, self.newline)));
+                let (id, _) = self.footnotes.last().unwrap();
+                write!(self.writer, "{}<sup><a href=\"#{}\">{}</a></sup>",
+                    render_inline(&attr.short_description), id, self.footnotes.len())?;
+            },
+            (Some(desc), _) => {
+                write!(self.writer, "<details>{}<summary>{}</summary>{}", self.newline,
+                    render_inline(&attr.short_description), self.newline)?;
+                self.write_block(desc)?;
+                write!(self.writer, "</details>")?;
+            },
+            (None, _) => write!(self.writer, "{}", render_inline(&attr.short_description))?,
+        };
+
+        write!(self.writer, "</td></tr>{}", self.newline)?;
+        Ok(())
+    }
+
+    fn open_attribute_table(&mut self) -> GeneratorResult<()> {
+        write!(self.writer, "<table class=\"xmldoc-attribute-table\">{}", self.newline)?;
+        write!(self.writer, "<thead><tr><th>Name</th><th>Required</th><th>Type</th><th>Default</th><th>Description</th></tr></thead>{}", self.newline)?;
+        write!(self.writer, "<tbody>{}", self.newline)?;
+        Ok(())
+    }
+
+    fn close_attribute_table(&mut self) -> GeneratorResult<()> {
+        write!(self.writer, "</tbody>{}</table>{}", self.newline, self.newline)?;
+        if !self.footnotes.is_empty() {
+            write!(self.writer, "<ol class=\"xmldoc-footnotes\">{}", self.newline)?;
+            for (id, html) in std::mem::take(&mut self.footnotes) {
+                write!(self.writer, "<li id=\"{}\">{}{}</li>{}", id, self.newline, html, self.newline)?;
+            }
+            write!(self.writer, "</ol>{}", self.newline)?;
+        }
+        Ok(())
+    }
+
+    fn close_list(&mut self) -> GeneratorResult<()> {
+        if self.list_open {
+            write!(self.writer, "</ul>{}", self.newline)?;
+            self.list_open = false;
+        }
+        Ok(())
+    }
+
+    fn write_index_entry(&mut self, entry: &IndexEntry) -> GeneratorResult<()> {
+        write!(self.writer, "<a href=\"#{}\"><code>{}:{}</code></a>",
+            entry.anchor, escape(&entry.namespace), escape(&entry.name))?;
+        Ok(())
+    }
+
+    fn write_index_node(&mut self, node: &IndexNode) -> GeneratorResult<()> {
+        write!(self.writer, "<li>")?;
+        self.write_index_entry(&node.entry)?;
+        if node.recursive {
+            self.write_markers(&["recursive"])?;
+        }
+
+        if !node.children.is_empty() {
+            write!(self.writer, "{}<ul>{}", self.newline, self.newline)?;
+            for child in &node.children {
+                self.write_index_node(child)?;
+            }
+            write!(self.writer, "</ul>")?;
+        }
+        write!(self.writer, "</li>{}", self.newline)?;
+        Ok(())
+    }
+
+    fn write_index_section(&mut self, class: &str, title: &str) -> GeneratorResult<()> {
+        write!(self.writer, "<div class=\"xmldoc-section {}\">{}", class, self.newline)?;
+        write!(self.writer, "<p class=\"xmldoc-section-title\"><em><strong>{}:</strong></em></p>{}", title, self.newline)?;
+        Ok(())
+    }
+}
+
+impl<'a> Renderer for HtmlRenderer<'a> {
+    fn document_begin(&mut self, root: &model::TagList) -> GeneratorResult<()> {
+        self.layout = self.options.attribute_layout_for(root);
+        Ok(())
+    }
+
+    fn introduction(&mut self, introduction: &Introduction) -> GeneratorResult<()> {
+        let level = self.options.level.get();
+        write!(self.writer, "<div class=\"xmldoc-introduction\">{}", self.newline)?;
+        write!(self.writer, "<h{} id=\"introduction\">{}</h{}>{}", level, escape(&introduction.title), level, self.newline)?;
+        if let Some(description) = &introduction.description {
+            write!(self.writer, "<div class=\"xmldoc-description\">{}", self.newline)?;
+            self.write_block(description)?;
+            write!(self.writer, "</div>{}", self.newline)?;
+        }
+
+        if !introduction.roots.is_empty() {
+            let title = if introduction.roots.len() == 1 { "Root tag" } else { "Root tags" };
+            self.write_index_section("xmldoc-roots", title)?;
+            write!(self.writer, "<ul>{}", self.newline)?;
+            for entry in &introduction.roots {
+                write!(self.writer, "<li>")?;
+                self.write_index_entry(entry)?;
+                write!(self.writer, "</li>{}", self.newline)?;
+            }
+            write!(self.writer, "</ul>{}</div>{}", self.newline, self.newline)?;
+        }
+        if !introduction.extensions.is_empty() || introduction.media_type.is_some() {
+            write!(self.writer, "<dl class=\"xmldoc-format\">{}", self.newline)?;
+            if !introduction.extensions.is_empty() {
+                let extensions = introduction.extensions.iter()
+                    .map(|ext| format!("<code>{}</code>", escape(ext)))
+                    .collect::<Vec<_>>();
+                write!(self.writer, "<dt>File extensions</dt><dd>{}</dd>{}", extensions.join(", "), self.newline)?;
+            }
+            if let Some(media_type) = &introduction.media_type {
+                write!(self.writer, "<dt>Media type</dt><dd><code>{}</code></dd>{}", escape(media_type), self.newline)?;
+            }
+            write!(self.writer, "</dl>{}", self.newline)?;
+        }
+
+        write!(self.writer, "</div>{}", self.newline)?;
+        Ok(())
+    }
+
+    fn index(&mut self, index: &Index) -> GeneratorResult<()> {
+        let level = self.options.level.get();
+        write!(self.writer, "<div class=\"xmldoc-index\">{}", self.newline)?;
+        write!(self.writer, "<h{} id=\"index\">Index</h{}>{}", level, level, self.newline)?;
+
+        self.write_index_section("xmldoc-index-alphabetical", "Alphabetical")?;
+        write!(self.writer, "<ul>{}", self.newline)?;
+        for entry in &index.alphabetical {
+            write!(self.writer, "<li>")?;
+            self.write_index_entry(entry)?;
+            write!(self.writer, "</li>{}", self.newline)?;
+        }
+        write!(self.writer, "</ul>{}</div>{}", self.newline, self.newline)?;
+
+        if !index.categories.is_empty() {
+            self.write_index_section("xmldoc-index-categories", "By category")?;
+            write!(self.writer, "<ul>{}", self.newline)?;
+            for category in &index.categories {
+                write!(self.writer, "<li class=\"xmldoc-category\">{}{}<ul>{}", escape(&category.name), self.newline, self.newline)?;
+                for entry in &category.tags {
+                    write!(self.writer, "<li>")?;
+                    self.write_index_entry(entry)?;
+                    write!(self.writer, "</li>{}", self.newline)?;
+                }
+                write!(self.writer, "</ul></li>{}", self.newline)?;
+            }
+            write!(self.writer, "</ul>{}</div>{}", self.newline, self.newline)?;
+        }
+
+        if !index.hierarchy.is_empty() {
+            self.write_index_section("xmldoc-index-hierarchy", "Hierarchy")?;
+            write!(self.writer, "<ul>{}", self.newline)?;
+            for node in &index.hierarchy {
+                self.write_index_node(node)?;
+            }
+            write!(self.writer, "</ul>{}</div>{}", self.newline, self.newline)?;
+        }
+
+        write!(self.writer, "</div>{}", self.newline)?;
+        Ok(())
+    }
+
+    fn graph(&mut self, format: GraphFormat, code: &str) -> GeneratorResult<()> {
+        let code = keep_block(&escape(code.trim_end())).replace('\n', self.newline);
+        match format {
+            GraphFormat::Mermaid =>
+                write!(self.writer, "<pre class=\"mermaid xmldoc-graph\">{}</pre>{}", code, self.newline)?,
+            GraphFormat::Dot =>
+                write!(self.writer, "<pre class=\"xmldoc-graph\"><code class=\"language-dot\">{}</code></pre>{}", code, self.newline)?,
+        };
+        Ok(())
+    }
+
+    fn global_attributes(&mut self, anchor: &str, attributes: &[model::Attribute]) -> GeneratorResult<()> {
+        self.tag_anchor = anchor.into();
+        let level = self.options.level.get();
+        write!(self.writer, "<div class=\"xmldoc-global-attributes\">{}", self.newline)?;
+        write!(self.writer, "<h{} id=\"{}\">Global attributes</h{}>{}", level, anchor, level, self.newline)?;
+        write!(self.writer, "<p>These attributes may be used on every tag.</p>{}", self.newline)?;
+
+        if self.layout != AttributeLayout::List {
+            self.open_attribute_table()?;
+        }
+        for attr in attributes {
+            self.attribute(attr, AttributeSource::Declared)?;
+        }
+        if self.layout != AttributeLayout::List {
+            self.close_attribute_table()?;
+        }
+        self.close_list()?;
+
+        write!(self.writer, "</div>{}", self.newline)?;
+        Ok(())
+    }
+
+    fn global_attributes_link(&mut self, link: &str) -> GeneratorResult<()> {
+        write!(self.writer, "<p class=\"xmldoc-global-link\">This tag also accepts the <a href=\"#{}\">global attributes</a>.</p>{}",
+            link, self.newline)?;
+        Ok(())
+    }
+
+    fn tag_begin(&mut self, anchor: &str, namespace: &str, tag: &model::Tag) -> GeneratorResult<()> {
+        self.tag_anchor = anchor.into();
+        let classes = if tag.is_deprecated { "xmldoc-tag xmldoc-deprecated" } else { "xmldoc-tag" };
+        let level = self.options.level.get();
+
+        write!(self.writer, "<div class=\"{}\">{}", classes, self.newline)?;
+        write!(self.writer, "<h{} id=\"{}\"><code>{}:{}</code></h{}>{}",
+            level, anchor, escape(namespace), escape(&tag.name), level, self.newline)?;
+        if tag.is_deprecated {
+            write!(self.writer, "<p class=\"xmldoc-marker xmldoc-deprecated\">Deprecated</p>{}", self.newline)?;
+        }
+        if tag.is_abstract {
+            write!(self.writer, "<p class=\"xmldoc-marker xmldoc-abstract\">Abstract</p>{}", self.newline)?;
+        }
+        if tag.is_recursive {
+            write!(self.writer, "<p class=\"xmldoc-marker xmldoc-recursive\">Recursive</p>{}", self.newline)?;
+        }
+
+        write!(self.writer, "<div class=\"xmldoc-description\">{}", self.newline)?;
+        self.write_block(&tag.description)?;
+        write!(self.writer, "</div>{}", self.newline)?;
+        Ok(())
+    }
+
+    fn tag_end(&mut self, _tag: &model::Tag) -> GeneratorResult<()> {
+        write!(self.writer, "</div>{}", self.newline)?;
+        Ok(())
+    }
+
+    fn section_begin(&mut self, section: Section, title: &str) -> GeneratorResult<()> {
+        let class = match section {
+            Section::Attributes => "xmldoc-attributes",
+            Section::Value => "xmldoc-value",
+            Section::Children => "xmldoc-children",
+            Section::Parents => "xmldoc-parents",
+            Section::Example => "xmldoc-example",
+        };
+        write!(self.writer, "<div class=\"xmldoc-section {}\">{}", class, self.newline)?;
+        write!(self.writer, "<p class=\"xmldoc-section-title\"><em><strong>{}:</strong></em></p>{}", escape(title), self.newline)?;
+
+        if section == Section::Attributes && self.layout != AttributeLayout::List {
+            self.open_attribute_table()?;
+        }
+        Ok(())
+    }
+
+    fn section_end(&mut self, section: Section) -> GeneratorResult<()> {
+        if section == Section::Attributes && self.layout != AttributeLayout::List {
+            self.close_attribute_table()?;
+        }
+        self.close_list()?;
+        write!(self.writer, "</div>{}", self.newline)?;
+        Ok(())
+    }
+
+    fn attribute(&mut self, attr: &model::Attribute, source: AttributeSource<'_>) -> GeneratorResult<()> {
+        match self.layout {
+            AttributeLayout::List => self.write_attribute_item(attr, source),
+            AttributeLayout::Table | AttributeLayout::Footnotes => self.write_attribute_row(attr, source),
+        }
+    }
+
+    fn value(&mut self, text: &str) -> GeneratorResult<()> {
+        self.write_block(text)
+    }
+
+    fn child(&mut self, link: Option<&str>, namespace: &str, name: &str, optional: bool, repeated: bool) -> GeneratorResult<()> {
+        self.open_list()?;
+
+        let requirement = if optional { "xmldoc-optional" } else { "xmldoc-required" };
+        let repetition = if repeated { " xmldoc-repeated" } else { "" };
+        write!(self.writer, "<li class=\"xmldoc-child {}{}\">", requirement, repetition)?;
+
+        if let Some(link) = link {
+            write!(self.writer, "<a href=\"#{}\"><code>{}:{}</code></a>",
+                link, escape(namespace), escape(name))?;
+        } else {
+            write!(self.writer, "<code class=\"xmldoc-unresolved\">{}:{}</code>", escape(namespace), escape(name))?;
+        }
+
+        let mut markers = vec![];
+        if optional { markers.push("optional"); }
+        if repeated { markers.push("repeated"); }
+        self.write_markers(&markers)?;
+
+        write!(self.writer, "</li>{}", self.newline)?;
+        Ok(())
+    }
+
+    fn parent(&mut self, link: &str, namespace: &str, name: &str) -> GeneratorResult<()> {
+        self.open_list()?;
+        write!(self.writer, "<li class=\"xmldoc-parent\"><a href=\"#{}\"><code>{}:{}</code></a></li>{}",
+            link, escape(namespace), escape(name), self.newline)?;
+        Ok(())
+    }
+
+    fn no_parents(&mut self) -> GeneratorResult<()> {
+        write!(self.writer, "<p>This tag has no possible parents!</p>{}", self.newline)?;
+        Ok(())
+    }
+
+    fn example(&mut self, code: &str) -> GeneratorResult<()> {
+        write!(self.writer, "<pre><code class=\"language-xml\">{}</code></pre>{}",
+            keep_block(&escape(code.trim_end())), self.newline)?;
+        Ok(())
+    }
+}
+
+
+/// Escape text for use in HTML content or double-quoted attribute values.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Encode empty lines as character references, which would otherwise end a raw HTML block.
+fn keep_block(text: &str) -> String {
+    let mut result = text.replace("\r\n", "\n");
+    while result.contains("\n\n") {
+        result = result.replace("\n\n", "\n&#10;");
+    }
+    result
+}
+
+/// Render Markdown `text` into HTML blocks, one per line.
+fn render_markdown(text: &str, newline: &str) -> String {
+    let mut rendered = String::new();
+    html::push_html(&mut rendered, Parser::new_ext(text, markdown_options()));
+    keep_block(rendered.trim_end()).replace('\n', newline) + newline
+}
+
+/// Render a single line of Markdown `text` into inline HTML.
+fn render_inline(text: &str) -> String {
+    let mut rendered = String::new();
+    html::push_html(&mut rendered, Parser::new_ext(text, markdown_options()));
+
+    let trimmed = rendered.trim_end();
+    match trimmed.strip_prefix("<p>").and_then(|t| t.strip_suffix("</p>")) {
+        Some(inner) if !inner.contains("<p>") => inner.into(),
+        _ => keep_block(trimmed),
+    }
+}
+
+fn markdown_options() -> Options {
+    Options::ENABLE_TABLES | Options::ENABLE_FOOTNOTES | Options::ENABLE_STRIKETHROUGH
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{load, options, render};
+    use super::super::{AttributeLayout, GeneratorOptions, OutputFormat};
+
+    #[test]
+    fn tags_render_as_semantic_html() {
+        let root = load("
+schema: { version: r1, namespace: ex }
+tags:
+  - id: list
+    description: A *list*.
+    attributes:
+      - { id: id, brief: Identifier. }
+      - { id: style, brief: Style., optional: true, description: Long **text**., default: plain }
+    children: [{ ref: item, multiple: true }, { ref: missing, optional: true }]
+  - { id: item, description: Item., value: Text., deprecated: true }
+");
+        assert_eq!(render(&root, &options(OutputFormat::Html)), r##"<div class="xmldoc-tag">
+<h1 id="exlist"><code>ex:list</code></h1>
+<div class="xmldoc-description">
+<p>A <em>list</em>.</p>
+</div>
+<div class="xmldoc-section xmldoc-attributes">
+<p class="xmldoc-section-title"><em><strong>Attributes:</strong></em></p>
+<ul>
+<li class="xmldoc-attribute xmldoc-required"><code>id</code> - Identifier.</li>
+<li class="xmldoc-attribute xmldoc-optional"><details>
+<summary><code>style</code> - Style. <span class="xmldoc-marker xmldoc-optional">optional</span></summary>
+<p>Long <strong>text</strong>.</p>
+<p class="xmldoc-default"><em>Default value:</em> plain</p>
+</details></li>
+</ul>
+</div>
+<div class="xmldoc-section xmldoc-children">
+<p class="xmldoc-section-title"><em><strong>Children:</strong></em></p>
+<ul>
+<li class="xmldoc-child xmldoc-required xmldoc-repeated"><a href="#exitem"><code>ex:item</code></a> <span class="xmldoc-marker xmldoc-repeated">repeated</span></li>
+<li class="xmldoc-child xmldoc-optional"><code class="xmldoc-unresolved">ex:missing</code> <span class="xmldoc-marker xmldoc-optional">optional</span></li>
+</ul>
+</div>
+<div class="xmldoc-section xmldoc-parents">
+<p class="xmldoc-section-title"><em><strong>Parents:</strong></em></p>
+<p>This tag has no possible parents!</p>
+</div>
+</div>
+<div class="xmldoc-tag xmldoc-deprecated">
+<h1 id="exitem"><code>ex:item</code></h1>
+<p class="xmldoc-marker xmldoc-deprecated">Deprecated</p>
+<div class="xmldoc-description">
+<p>Item.</p>
+</div>
+<div class="xmldoc-section xmldoc-value">
+<p class="xmldoc-section-title"><em><strong>Value:</strong></em></p>
+<p>Text.</p>
+</div>
+<div class="xmldoc-section xmldoc-parents">
+<p class="xmldoc-section-title"><em><strong>Parents:</strong></em></p>
+<ul>
+<li class="xmldoc-parent"><a href="#exlist"><code>ex:list</code></a></li>
+</ul>
+</div>
+</div>
+"##);
+    }
+
+    #[test]
+    fn footnotes_layout_links_descriptions_below_the_table() {
+        let root = load("
+schema: { version: r1, namespace: ex }
+tags:
+  - id: item
+    description: Item.
+    attributes: [{ id: style, brief: Style a | b., optional: true, description: \"One.\\n\\nTwo.\" }]
+");
+        let options = GeneratorOptions { attribute_layout: Some(AttributeLayout::Footnotes), ..options(OutputFormat::Html) };
+        let output = render(&root, &options);
+        assert!(output.contains(r##"<td>Style a | b.<sup><a href="#fn-exitem-style">1</a></sup></td></tr>"##), "{}", output);
+        assert!(output.contains("</table>\n<ol class=\"xmldoc-footnotes\">\n<li id=\"fn-exitem-style\">\n<p>One.</p>\n<p>Two.</p>\n</li>\n</ol>\n"), "{}", output);
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+use serde::Serialize;
+
+use super::{Anchors, TagOrder};
+use crate::model::{self, TagId};
+
+
+/// Category of tags which don't declare one, listed last.
+const UNCATEGORIZED: &str = "Other";
+
+
+/// Navigation over all tags of a document, linked to their sections.
+#[derive(Debug, Default, Serialize)]
+pub struct Index {
+    /// All tags, sorted by name.
+    pub alphabetical: Vec<IndexEntry>,
+    /// Tags grouped by category, empty unless at least one tag has a category.
+    pub categories: Vec<IndexCategory>,
+    /// Hierarchy of resolved children, starting from the root tags.
+    pub hierarchy: Vec<IndexNode>,
+}
+
+/// Link to a single tag section.
+#[derive(Debug, Default, Serialize)]
+pub struct IndexEntry {
+    pub namespace: String,
+    pub name: String,
+    pub anchor: String,
+}
+
+/// Named group of tags, sorted by name.
+#[derive(Debug, Default, Serialize)]
+pub struct IndexCategory {
+    pub name: String,
+    pub tags: Vec<IndexEntry>,
+}
+
+/// Tag within the hierarchy tree.
+#[derive(Debug, Default, Serialize)]
+pub struct IndexNode {
+    #[serde(flatten)]
+    pub entry: IndexEntry,
+    /// Whether the tag is its own ancestor here, in which case its children are omitted.
+    pub recursive: bool,
+    pub children: Vec<IndexNode>,
+}
+
+
+impl Index {
+    /// Build the index of `root`, linking to sections through `anchors`.
+    pub fn new(root: &model::TagList, anchors: &Anchors) -> Self {
+        let entry = |tag: &model::Tag| IndexEntry {
+            namespace: root.namespace.to_string(),
+            name: tag.name.to_string(),
+            anchor: anchors.get(tag.id).into(),
+        };
+
+        let mut sorted = TagOrder::Alphabetical.arrange(root);
+        sorted.retain(|tag| anchors.find(tag.id).is_some());
+
+        let mut categories = vec![];
+        if sorted.iter().any(|tag| tag.category.is_some()) {
+            let mut grouped = BTreeMap::<&str, Vec<IndexEntry>>::new();
+            let mut uncategorized = vec![];
+            for tag in &sorted {
+                match tag.category.as_deref() {
+                    Some(category) => grouped.entry(category).or_default().push(entry(tag)),
+                    None => uncategorized.push(entry(tag)),
+                }
+            }
+
+            categories = grouped.into_iter()
+                .map(|(name, tags)| IndexCategory { name: name.into(), tags })
+                .collect();
+            if !uncategorized.is_empty() {
+                categories.push(IndexCategory { name: UNCATEGORIZED.into(), tags: uncategorized });
+            }
+        }
+
+        let mut path = vec![];
+        let hierarchy = root.roots.iter()
+            .map(|id| node(root, &entry, *id, &mut path))
+            .collect();
+
+        Index {
+            alphabetical: sorted.into_iter().map(entry).collect(),
+            categories,
+            hierarchy,
+        }
+    }
+}
+
+/// Build the subtree of tag `id`, with `path` holding its ancestors.
+fn node(root: &model::TagList, entry: &dyn Fn(&model::Tag) -> IndexEntry, id: TagId, path: &mut Vec<TagId>) -> IndexNode {
+    let tag = &root[id];
+    if path.contains(&id) {
+        return IndexNode { entry: entry(tag), recursive: true, children: vec![] };
+    }
+
+    path.push(id);
+    let children = tag.children.iter()
+        .filter_map(|child| match &child.reference {
+            model::ChildInternal::Resolved { id } => Some(*id),
+            model::ChildInternal::Abstract { .. } | model::ChildInternal::Unresolved { .. } => None,
+        })
+        .map(|child_id| node(root, entry, child_id, path))
+        .collect();
+    path.pop();
+
+    IndexNode { entry: entry(tag), recursive: false, children }
+}
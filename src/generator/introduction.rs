@@ -0,0 +1,50 @@
+use serde::Serialize;
+
+use super::Anchors;
+use super::index::IndexEntry;
+use crate::model;
+
+
+/// Heading of the introduction if the tag list has no title.
+const UNTITLED: &str = "Overview";
+
+
+/// Document-level introduction preceding the tag reference.
+#[derive(Debug, Default, Serialize)]
+pub struct Introduction {
+    /// Title of the document format, or a generic heading.
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Links to the root tags, in declared or source order.
+    pub roots: Vec<IndexEntry>,
+    /// File extensions, including the leading dot.
+    pub extensions: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+}
+
+impl Introduction {
+    /// Build the introduction of `root`, linking to root tags through `anchors`,
+    /// unless the tag list has no document-level description.
+    pub fn new(root: &model::TagList, anchors: &Anchors) -> Option<Self> {
+        let overview = &root.overview;
+        if overview.is_empty() {
+            return None;
+        }
+
+        Some(Introduction {
+            title: overview.title.as_deref().unwrap_or(UNTITLED).into(),
+            description: overview.description.clone(),
+            roots: root.roots.iter()
+                .map(|id| IndexEntry {
+                    namespace: root.namespace.to_string(),
+                    name: root[*id].name.to_string(),
+                    anchor: anchors.get(*id).into(),
+                })
+                .collect(),
+            extensions: overview.extensions.iter().map(|ext| format!(".{}", ext)).collect(),
+            media_type: overview.media_type.as_ref().map(ToString::to_string),
+        })
+    }
+}
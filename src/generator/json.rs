@@ -0,0 +1,277 @@
+use std::io;
+use serde::Serialize;
+
+use super::{Anchors, GeneratorOptions, GeneratorResult};
+use crate::model;
+
+
+/// Version of the JSON model layout, bumped on incompatible changes.
+pub const MODEL_VERSION: u32 = 1;
+
+/// Identifier of the JSON model layout, present in every document.
+const MODEL_FORMAT: &str = "mdbook-xmldoc/model";
+
+
+/// Root of the JSON model document.
+#[derive(Debug, Serialize)]
+pub struct ModelView<'a> {
+    /// Always `mdbook-xmldoc/model`.
+    pub format: &'static str,
+    /// Always [`MODEL_VERSION`].
+    pub version: u32,
+    /// Tag list schema version the model was loaded from.
+    pub schema: &'static str,
+    pub namespace: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attribute_layout: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<&'a str>,
+    /// File extensions of documents, without the leading dot.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<&'a str>,
+    /// Declared root tags, or tags without possible parents in source order.
+    pub roots: Vec<TagRefView<'a>>,
+    /// Attributes every tag accepts, also listed with each tag unless shadowed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub global_attributes: Vec<AttributeView<'a>>,
+    /// All tags in source order, where `id` is the position within this array.
+    pub tags: Vec<TagView<'a>>,
+}
+
+/// Reference to a tag of the same model.
+#[derive(Debug, Serialize)]
+pub struct TagRefView<'a> {
+    pub id: usize,
+    pub name: &'a str,
+}
+
+/// A single tag, with its children resolved and parents computed.
+#[derive(Debug, Serialize)]
+pub struct TagView<'a> {
+    pub id: usize,
+    pub name: &'a str,
+    /// Link target of the tag section in documents generated with the same options,
+    /// missing for abstract tags unless they're described too.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<&'a str>,
+    pub description: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<&'a str>,
+    pub deprecated: bool,
+    /// Whether the tag may be nested within itself.
+    pub recursive: bool,
+    /// Whether the tag only serves as a base for others.
+    pub r#abstract: bool,
+    /// Base tags this one inherits from, in order of precedence.
+    pub extends: Vec<TagRefView<'a>>,
+    /// Attributes including inherited, group and global ones, with where they were declared.
+    pub attributes: Vec<AttributeView<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub example: Option<&'a str>,
+    pub children: Vec<ChildView<'a>>,
+    /// Tags which may contain this one, in source order.
+    pub parents: Vec<TagRefView<'a>>,
+}
+
+/// A single attribute.
+#[derive(Debug, Serialize)]
+pub struct AttributeView<'a> {
+    pub name: &'a str,
+    pub brief: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<&'a str>,
+    pub optional: bool,
+    pub deprecated: bool,
+    /// Base tag declaring the attribute, if it's inherited.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inherited_from: Option<TagRefView<'a>>,
+    /// Attribute group declaring the attribute, if the tag includes it from one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<&'a str>,
+    /// Whether the attribute is one of the global attributes.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub global: bool,
+}
+
+/// A child reference, with `id` only present if `resolved`.
+#[derive(Debug, Serialize)]
+pub struct ChildView<'a> {
+    pub name: &'a str,
+    pub resolved: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<usize>,
+    /// Whether the reference is to an abstract tag, which documents contain tags extending instead.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub r#abstract: bool,
+    pub optional: bool,
+    pub repeated: bool,
+}
+
+
+impl<'a> ModelView<'a> {
+    /// Build the JSON view of `root`, with tag anchors taken from `anchors`.
+    pub fn new(root: &'a model::TagList, anchors: &'a Anchors) -> Self {
+        let tag_ref = |id: model::TagId| TagRefView { id: id.index(), name: root[id].name.as_str() };
+        let attribute = |attr: &'a model::Attribute| AttributeView {
+            name: &attr.name,
+            brief: &attr.short_description,
+            description: attr.long_description.as_deref(),
+            expected: attr.expected_value.as_deref(),
+            default: attr.default_value.as_deref(),
+            optional: attr.is_optional,
+            deprecated: attr.is_deprecated,
+            inherited_from: match attr.origin {
+                model::AttributeOrigin::Inherited { base } => Some(tag_ref(base)),
+                _ => None,
+            },
+            group: match attr.origin {
+                model::AttributeOrigin::Group { group } => Some(root.attribute_groups[group].name.as_str()),
+                _ => None,
+            },
+            global: attr.origin == model::AttributeOrigin::Global,
+        };
+
+        let tags = root.iter()
+            .map(|tag| TagView {
+                id: tag.id.index(),
+                name: &tag.name,
+                anchor: anchors.find(tag.id),
+                description: &tag.description,
+                category: tag.category.as_deref(),
+                deprecated: tag.is_deprecated,
+                recursive: tag.is_recursive,
+                r#abstract: tag.is_abstract,
+                extends: tag.bases.iter().map(|id| tag_ref(*id)).collect(),
+                attributes: tag.attributes.iter().map(attribute).collect(),
+                value: tag.value.as_deref(),
+                example: tag.example.as_deref(),
+                children: tag.children.iter()
+                    .map(|child| {
+                        let (name, id) = match &child.reference {
+                            model::ChildInternal::Resolved { id } | model::ChildInternal::Abstract { id } =>
+                                (root[*id].name.as_str(), Some(id.index())),
+                            model::ChildInternal::Unresolved { name } => (name.as_str(), None),
+                        };
+                        ChildView {
+                            name,
+                            resolved: id.is_some(),
+                            id,
+                            r#abstract: matches!(child.reference, model::ChildInternal::Abstract { .. }),
+                            optional: child.is_optional,
+                            repeated: child.is_repeatable,
+                        }
+                    })
+                    .collect(),
+                parents: root.parents.get(&tag.id)
+                    .map(|parents| parents.iter().map(|id| tag_ref(*id)).collect())
+                    .unwrap_or_default(),
+            })
+            .collect();
+
+        ModelView {
+            format: MODEL_FORMAT,
+            version: MODEL_VERSION,
+            schema: model::VERSION,
+            namespace: &root.namespace,
+            attribute_layout: root.attribute_layout.map(|layout| layout.name()),
+            title: root.overview.title.as_deref(),
+            description: root.overview.description.as_deref(),
+            extensions: root.overview.extensions.iter().map(|ext| ext.as_str()).collect(),
+            media_type: root.overview.media_type.as_deref(),
+            roots: root.roots.iter().map(|id| tag_ref(*id)).collect(),
+            global_attributes: root.global_attributes.iter().map(attribute).collect(),
+            tags,
+        }
+    }
+}
+
+
+/// Write the JSON model of `root` into `writer`.
+pub fn write(root: &model::TagList, options: &GeneratorOptions, writer: &mut dyn io::Write) -> GeneratorResult<()> {
+    let ordered_tags = options.arrange(root);
+    let anchors = Anchors::new(&root.namespace, ordered_tags.iter().map(|tag| (tag.id, tag.name.as_str())));
+
+    let mut text = serde_json::to_string_pretty(&ModelView::new(root, &anchors))
+        .map_err(io::Error::from)?;
+    text.push('\n');
+
+    match options.crlf {
+        true => write!(writer, "{}", text.replace('\n', "\r\n"))?,
+        false => write!(writer, "{}", text)?,
+    };
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::super::tests::{load, options, render};
+    use super::super::OutputFormat;
+
+    #[test]
+    fn model_is_dumped_with_format_and_version() {
+        let root = load("
+schema: { version: r1, namespace: ex, title: Example }
+global_attributes: [{ id: lang, brief: Language., optional: true }]
+tags:
+  - { id: base, abstract: true, description: Base., attributes: [{ id: kind, brief: Kind., default: a, optional: true }] }
+  - id: list
+    extend: [base]
+    description: List.
+    children: [{ ref: item, multiple: true }, { ref: base, optional: true }, { ref: missing, optional: true }]
+  - { id: item, description: Item., value: Text. }
+");
+        let text = render(&root, &options(OutputFormat::Json));
+        assert!(text.ends_with("}\n"));
+
+        let lang = json!({ "name": "lang", "brief": "Language.", "optional": true, "deprecated": false, "global": true });
+        let kind = json!({ "name": "kind", "brief": "Kind.", "default": "a", "optional": true, "deprecated": false });
+        let mut inherited_kind = kind.clone();
+        inherited_kind["inherited_from"] = json!({ "id": 0, "name": "base" });
+
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&text).unwrap(), json!({
+            "format": "mdbook-xmldoc/model",
+            "version": super::MODEL_VERSION,
+            "schema": "r1",
+            "namespace": "ex",
+            "title": "Example",
+            "roots": [{ "id": 1, "name": "list" }],
+            "global_attributes": [lang],
+            "tags": [
+                {
+                    "id": 0, "name": "base", "description": "Base.",
+                    "deprecated": false, "recursive": false, "abstract": true,
+                    "extends": [], "attributes": [kind, lang], "children": [], "parents": [],
+                },
+                {
+                    "id": 1, "name": "list", "anchor": "exlist", "description": "List.",
+                    "deprecated": false, "recursive": false, "abstract": false,
+                    "extends": [{ "id": 0, "name": "base" }],
+                    "attributes": [inherited_kind, lang],
+                    "children": [
+                        { "name": "item", "resolved": true, "id": 2, "optional": false, "repeated": true },
+                        { "name": "base", "resolved": true, "id": 0, "abstract": true, "optional": true, "repeated": false },
+                        { "name": "missing", "resolved": false, "optional": true, "repeated": false },
+                    ],
+                    "parents": [],
+                },
+                {
+                    "id": 2, "name": "item", "anchor": "exitem", "description": "Item.",
+                    "deprecated": false, "recursive": false, "abstract": false,
+                    "extends": [], "attributes": [lang], "value": "Text.", "children": [],
+                    "parents": [{ "id": 1, "name": "list" }],
+                },
+            ],
+        }));
+    }
+}
@@ -0,0 +1,444 @@
+use std::io;
+use smallvec::SmallVec;
+
+use super::{AnchorStyle, AttributeLayout, AttributeSource, GeneratorOptions, GeneratorResult, Index, Introduction, Renderer, Section};
+use super::index::{IndexEntry, IndexNode};
+use crate::graph::GraphFormat;
+use crate::model;
+
+
+/// [`Renderer`] producing the opinionated Markdown layout.
+pub struct MarkdownRenderer<'a> {
+    options: &'a GeneratorOptions,
+    writer: &'a mut dyn io::Write,
+    newline: &'static str,
+    newblock: &'static str,
+    /// Whether the current parents section was written as a paragraph rather than a list.
+    parentless: bool,
+    /// Attribute layout resolved for the current document.
+    layout: AttributeLayout,
+    /// Anchor of the current tag, used to make footnote labels unique.
+    tag_anchor: String,
+    /// Footnote definitions to write after the current attribute table.
+    footnotes: Vec<String>,
+}
+
+impl<'a> MarkdownRenderer<'a> {
+    /// Create a renderer writing into `writer`.
+    pub fn new(options: &'a GeneratorOptions, writer: &'a mut dyn io::Write) -> Self {
+        MarkdownRenderer {
+            options,
+            writer,
+            newline: if options.crlf { "\r\n" } else { "\n" },
+            newblock: if options.crlf { "\r\n\r\n" } else { "\n\n" },
+            parentless: false,
+            layout: options.attribute_layout.unwrap_or_default(),
+            tag_anchor: String::new(),
+            footnotes: vec![],
+        }
+    }
+
+    fn write_paragraph(&mut self, text: &str) -> GeneratorResult<()> {
+        write!(self.writer, "{}{}", text, self.newblock)?;
+        Ok(())
+    }
+
+    fn write_modifiers(&mut self, modifiers: &[&str]) -> GeneratorResult<()> {
+        if !modifiers.is_empty() {
+            write!(self.writer, " _({})_", modifiers.join(", "))?;
+        }
+        Ok(())
+    }
+
+    fn write_attribute_item(&mut self, attr: &model::Attribute, source: AttributeSource<'_>) -> GeneratorResult<()> {
+        write!(self.writer, "* `{}` - {}", attr.name, attr.short_description)?;
+
+        let inherited = inherited_note(source);
+        let mut modifiers = SmallVec::<[&str; 3]>::new();
+        if attr.is_optional { modifiers.push("optional"); }
+        if attr.is_deprecated { modifiers.push("deprecated"); }
+        if let Some(note) = &inherited { modifiers.push(note); }
+        self.write_modifiers(&modifiers)?;
+        write!(self.writer, "{}", self.newline)?;
+
+        if let Some(desc) = &attr.long_description {
+            write!(self.writer, "  * {}{}", desc, self.newline)?;
+        }
+
+        if let Some(expected) = &attr.expected_value {
+            write!(self.writer, "  * _Expected value:_ {}{}", expected, self.newline)?;
+        }
+
+        if let Some(r#default) = &attr.default_value {
+            write!(self.writer, "  * _Default value:_ {}{}", r#default, self.newline)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_attribute_row(&mut self, attr: &model::Attribute, source: AttributeSource<'_>) -> GeneratorResult<()> {
+        let inherited = inherited_note(source);
+        let mut modifiers = SmallVec::<[&str; 2]>::new();
+        if attr.is_deprecated { modifiers.push("deprecated"); }
+        if let Some(note) = &inherited { modifiers.push(note); }
+        let modifiers = match modifiers.is_empty() {
+            true => String::new(),
+            false => format!(" _({})_", table_cell(&modifiers.join(", "))),
+        };
+        let required = if attr.is_optional { "no" } else { "yes" };
+        let expected = attr.expected_value.as_deref().map(table_cell).unwrap_or_default();
+        let r#default = attr.default_value.as_deref().map(table_cell).unwrap_or_default();
+
+        let mut description = table_cell(&attr.short_description);
+        if let Some(desc) = &attr.long_description {
+            match self.layout {
+                AttributeLayout::Footnotes => {
+                    let label = format!("{}-{}", self.tag_anchor, attr.name.to_lowercase());
+                    description.push_str(&format!(" [^{}]", label));
+                    self.footnotes.push(format!("[^{}]: {}", label, footnote_text(desc, self.newline)));
+                },
+                _ => {
+                    description.push_str("<br><br>");
+                    description.push_str(&table_cell(desc));
+                },
+            }
+        }
+
+        write!(self.writer, "| `{}`{} | {} | {} | {} | {} |{}",
+            attr.name, modifiers, required, expected, r#default, description, self.newline)?;
+        Ok(())
+    }
+
+    fn write_attribute_table_head(&mut self) -> GeneratorResult<()> {
+        write!(self.writer, "| Name | Required | Type | Default | Description |{}", self.newline)?;
+        write!(self.writer, "|------|----------|------|---------|-------------|{}", self.newline)?;
+        Ok(())
+    }
+
+    fn write_index_entry(&mut self, indent: usize, entry: &IndexEntry) -> GeneratorResult<()> {
+        write!(self.writer, "{:indent$}* [`{}:{}`](#{})", "", entry.namespace, entry.name, entry.anchor, indent = indent)?;
+        Ok(())
+    }
+
+    fn write_index_node(&mut self, indent: usize, node: &IndexNode) -> GeneratorResult<()> {
+        self.write_index_entry(indent, &node.entry)?;
+        if node.recursive {
+            self.write_modifiers(&["recursive"])?;
+        }
+        write!(self.writer, "{}", self.newline)?;
+
+        for child in &node.children {
+            self.write_index_node(indent + 2, child)?;
+        }
+        Ok(())
+    }
+}
+
+/// Describe where an inherited attribute comes from, linking to the base tag if possible.
+fn inherited_note(source: AttributeSource<'_>) -> Option<String> {
+    match source {
+        AttributeSource::Declared => None,
+        AttributeSource::Inherited { link: Some(link), namespace, name } =>
+            Some(format!("inherited from [`{}:{}`](#{})", namespace, name, link)),
+        AttributeSource::Inherited { link: None, namespace, name } =>
+            Some(format!("inherited from `{}:{}`", namespace, name)),
+    }
+}
+
+/// Fit Markdown text into a single table cell.
+pub(super) fn table_cell(text: &str) -> String {
+    text.trim()
+        .replace("\r\n", "\n")
+        .replace("\n\n", "<br><br>")
+        .replace('\n', " ")
+        .replace('|', "\\|")
+}
+
+/// Indent continuation lines of a footnote definition, keeping its paragraphs.
+pub(super) fn footnote_text(text: &str, newline: &str) -> String {
+    text.trim()
+        .lines()
+        .enumerate()
+        .map(|(index, line)| match (index, line.trim().is_empty()) {
+            (0, _) | (_, true) => line.trim_end().to_string(),
+            _ => format!("    {}", line.trim_end()),
+        })
+        .collect::<Vec<_>>()
+        .join(newline)
+}
+
+impl<'a> Renderer for MarkdownRenderer<'a> {
+    fn document_begin(&mut self, root: &model::TagList) -> GeneratorResult<()> {
+        self.layout = self.options.attribute_layout_for(root);
+        Ok(())
+    }
+
+    fn introduction(&mut self, introduction: &Introduction) -> GeneratorResult<()> {
+        write!(self.writer, "{} {}{}", self.options.level.get_prefix(), introduction.title, self.newblock)?;
+        if let Some(description) = &introduction.description {
+            self.write_paragraph(description)?;
+        }
+
+        if !introduction.roots.is_empty() {
+            let title = if introduction.roots.len() == 1 { "Root tag" } else { "Root tags" };
+            write!(self.writer, "_**{}:**_{}", title, self.newblock)?;
+            for entry in &introduction.roots {
+                self.write_index_entry(0, entry)?;
+                write!(self.writer, "{}", self.newline)?;
+            }
+            write!(self.writer, "{}", self.newline)?;
+        }
+        if !introduction.extensions.is_empty() {
+            let extensions = introduction.extensions.iter().map(|ext| format!("`{}`", ext)).collect::<Vec<_>>();
+            write!(self.writer, "_**File extensions:**_ {}{}", extensions.join(", "), self.newblock)?;
+        }
+        if let Some(media_type) = &introduction.media_type {
+            write!(self.writer, "_**Media type:**_ `{}`{}", media_type, self.newblock)?;
+        }
+        Ok(())
+    }
+
+    fn index(&mut self, index: &Index) -> GeneratorResult<()> {
+        write!(self.writer, "{} Index{}", self.options.level.get_prefix(), self.newblock)?;
+
+        write!(self.writer, "_**Alphabetical:**_{}", self.newblock)?;
+        for entry in &index.alphabetical {
+            self.write_index_entry(0, entry)?;
+            write!(self.writer, "{}", self.newline)?;
+        }
+        write!(self.writer, "{}", self.newline)?;
+
+        if !index.categories.is_empty() {
+            write!(self.writer, "_**By category:**_{}", self.newblock)?;
+            for category in &index.categories {
+                write!(self.writer, "* {}{}", category.name, self.newline)?;
+                for entry in &category.tags {
+                    self.write_index_entry(2, entry)?;
+                    write!(self.writer, "{}", self.newline)?;
+                }
+            }
+            write!(self.writer, "{}", self.newline)?;
+        }
+
+        if !index.hierarchy.is_empty() {
+            write!(self.writer, "_**Hierarchy:**_{}", self.newblock)?;
+            for node in &index.hierarchy {
+                self.write_index_node(0, node)?;
+            }
+            write!(self.writer, "{}", self.newline)?;
+        }
+        Ok(())
+    }
+
+    fn graph(&mut self, format: GraphFormat, code: &str) -> GeneratorResult<()> {
+        let info = match format {
+            GraphFormat::Mermaid => "mermaid",
+            GraphFormat::Dot => "dot process",
+        };
+        let code = code.trim_end().replace('\n', self.newline);
+        write!(self.writer, "```{}{}{}{}```{}", info, self.newline, code, self.newline, self.newblock)?;
+        Ok(())
+    }
+
+    fn global_attributes(&mut self, anchor: &str, attributes: &[model::Attribute]) -> GeneratorResult<()> {
+        self.tag_anchor = anchor.into();
+        let prefix = self.options.level.get_prefix();
+        match self.options.anchor_style {
+            AnchorStyle::Implicit =>
+                write!(self.writer, "{} Global attributes{}", prefix, self.newblock)?,
+            AnchorStyle::Attribute =>
+                write!(self.writer, "{} Global attributes {{#{}}}{}", prefix, anchor, self.newblock)?,
+            AnchorStyle::Html =>
+                write!(self.writer, "<a id=\"{}\"></a>{}{} Global attributes{}", anchor, self.newblock, prefix, self.newblock)?,
+        };
+        self.write_paragraph("_These attributes may be used on every tag._")?;
+
+        if self.layout != AttributeLayout::List {
+            self.write_attribute_table_head()?;
+        }
+        for attr in attributes {
+            self.attribute(attr, AttributeSource::Declared)?;
+        }
+        self.section_end(Section::Attributes)
+    }
+
+    fn global_attributes_link(&mut self, link: &str) -> GeneratorResult<()> {
+        self.write_paragraph(&format!("_This tag also accepts the [global attributes](#{})._", link))
+    }
+
+    fn tag_begin(&mut self, anchor: &str, namespace: &str, tag: &model::Tag) -> GeneratorResult<()> {
+        self.tag_anchor = anchor.into();
+        let prefix = self.options.level.get_prefix();
+        match self.options.anchor_style.for_anchor(anchor, namespace, &tag.name) {
+            AnchorStyle::Implicit =>
+                write!(self.writer, "{} `{}:{}`{}", prefix, namespace, tag.name, self.newblock)?,
+            AnchorStyle::Attribute =>
+                write!(self.writer, "{} `{}:{}` {{#{}}}{}", prefix, namespace, tag.name, anchor, self.newblock)?,
+            AnchorStyle::Html =>
+                write!(self.writer, "<a id=\"{}\"></a>{}{} `{}:{}`{}", anchor, self.newblock, prefix, namespace, tag.name, self.newblock)?,
+        };
+        if tag.is_deprecated {
+            self.write_paragraph("_This tag is deprecated._")?;
+        }
+        if tag.is_abstract {
+            self.write_paragraph("_This tag is abstract, it only serves as a base for other tags._")?;
+        }
+        if tag.is_recursive {
+            self.write_paragraph("_This tag is recursive, it may be nested within itself._")?;
+        }
+        self.write_paragraph(&tag.description)
+    }
+
+    fn section_begin(&mut self, section: Section, title: &str) -> GeneratorResult<()> {
+        if section == Section::Parents {
+            self.parentless = false;
+        }
+        write!(self.writer, "_**{}:**_{}", title, self.newblock)?;
+
+        if section == Section::Attributes && self.layout != AttributeLayout::List {
+            self.write_attribute_table_head()?;
+        }
+        Ok(())
+    }
+
+    fn section_end(&mut self, section: Section) -> GeneratorResult<()> {
+        match section {
+            Section::Attributes if self.layout != AttributeLayout::List => {
+                write!(self.writer, "{}", self.newline)?;
+                for footnote in std::mem::take(&mut self.footnotes) {
+                    write!(self.writer, "{}{}", footnote, self.newblock)?;
+                }
+            },
+            Section::Attributes | Section::Children => write!(self.writer, "{}", self.newblock)?,
+            Section::Parents if !self.parentless => write!(self.writer, "{}", self.newblock)?,
+            _ => (),
+        };
+        Ok(())
+    }
+
+    fn attribute(&mut self, attr: &model::Attribute, source: AttributeSource<'_>) -> GeneratorResult<()> {
+        match self.layout {
+            AttributeLayout::List => self.write_attribute_item(attr, source),
+            AttributeLayout::Table | AttributeLayout::Footnotes => self.write_attribute_row(attr, source),
+        }
+    }
+
+    fn value(&mut self, text: &str) -> GeneratorResult<()> {
+        self.write_paragraph(text)
+    }
+
+    fn child(&mut self, link: Option<&str>, namespace: &str, name: &str, optional: bool, repeated: bool) -> GeneratorResult<()> {
+        if let Some(link) = link {
+            write!(self.writer, "* [`{}:{}`](#{})", namespace, name, link)?;
+        } else {
+            write!(self.writer, "* `{}:{}`", namespace, name)?;
+        }
+
+        let mut modifiers = SmallVec::<[&'static str; 2]>::new();
+        if optional { modifiers.push("optional"); }
+        if repeated { modifiers.push("repeated"); }
+        self.write_modifiers(&modifiers)?;
+
+        write!(self.writer, "{}", self.newline)?;
+        Ok(())
+    }
+
+    fn parent(&mut self, link: &str, namespace: &str, name: &str) -> GeneratorResult<()> {
+        write!(self.writer, "* [`{}:{}`](#{}){}", namespace, name, link, self.newline)?;
+        Ok(())
+    }
+
+    fn no_parents(&mut self) -> GeneratorResult<()> {
+        self.parentless = true;
+        self.write_paragraph("This tag has no possible parents!")
+    }
+
+    fn example(&mut self, code: &str) -> GeneratorResult<()> {
+        write!(self.writer, "```xml{}{}{}```{}", self.newline, code.trim_end(), self.newline, self.newblock)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::{load, options, render};
+    use super::super::OutputFormat;
+
+    const TAG_LIST: &str = r#"
+schema: { version: r1, namespace: ex }
+tags:
+  - id: item
+    description: Item.
+    attributes:
+      - { id: id, brief: Identifier. }
+      - { id: style, brief: "Style a | b.", optional: true, expected: "`a` or `b`", default: a, description: "First line\nsame paragraph.\n\nSecond paragraph." }
+      - { id: old, brief: Old., optional: true, deprecated: true }
+"#;
+
+    fn render_with(layout: AttributeLayout) -> String {
+        let options = GeneratorOptions { attribute_layout: Some(layout), ..options(OutputFormat::Markdown) };
+        render(&load(TAG_LIST), &options)
+    }
+
+    #[test]
+    fn table_cells_stay_on_one_line() {
+        assert_eq!(table_cell(" a | b "), "a \\| b");
+        assert_eq!(table_cell("one\r\ntwo\n\nthree\n"), "one two<br><br>three");
+    }
+
+    #[test]
+    fn footnote_continuation_lines_are_indented() {
+        assert_eq!(footnote_text("one\ntwo\n\nthree\n", "\n"), "one\n    two\n\n    three");
+        assert_eq!(footnote_text("one\ntwo", "\r\n"), "one\r\n    two");
+    }
+
+    #[test]
+    fn table_layout_folds_long_descriptions() {
+        assert_eq!(render_with(AttributeLayout::Table), "\
+# `ex:item`
+
+Item.
+
+_**Attributes:**_
+
+| Name | Required | Type | Default | Description |
+|------|----------|------|---------|-------------|
+| `id` | yes |  |  | Identifier. |
+| `style` | no | `a` or `b` | a | Style a \\| b.<br><br>First line same paragraph.<br><br>Second paragraph. |
+| `old` _(deprecated)_ | no |  |  | Old. |
+
+_**Parents:**_
+
+This tag has no possible parents!
+
+");
+    }
+
+    #[test]
+    fn footnotes_layout_moves_long_descriptions_below_the_table() {
+        assert_eq!(render_with(AttributeLayout::Footnotes), "\
+# `ex:item`
+
+Item.
+
+_**Attributes:**_
+
+| Name | Required | Type | Default | Description |
+|------|----------|------|---------|-------------|
+| `id` | yes |  |  | Identifier. |
+| `style` | no | `a` or `b` | a | Style a \\| b. [^exitem-style] |
+| `old` _(deprecated)_ | no |  |  | Old. |
+
+[^exitem-style]: First line
+    same paragraph.
+
+    Second paragraph.
+
+_**Parents:**_
+
+This tag has no possible parents!
+
+");
+    }
+}
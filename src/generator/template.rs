@@ -0,0 +1,480 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use handlebars::Handlebars;
+use serde::Serialize;
+
+use super::markdown::{footnote_text, table_cell};
+use super::{AnchorStyle, AttributeLayout, AttributeSource, GeneratorError, GeneratorOptions, GeneratorResult, Index, Introduction, Renderer, Section};
+use crate::graph::GraphFormat;
+use crate::model;
+
+
+/// Built-in templates reproducing the Markdown layout, by name.
+/// The `tag` template is rendered once per tag, `introduction`, `index`, `graph` and `global-attributes`
+/// once before them, others are partials.
+const DEFAULT_TEMPLATES: [(&str, &str); 11] = [
+    ("tag", include_str!("templates/tag.hbs")),
+    ("attribute", include_str!("templates/attribute.hbs")),
+    ("attribute-row", include_str!("templates/attribute-row.hbs")),
+    ("child", include_str!("templates/child.hbs")),
+    ("parent", include_str!("templates/parent.hbs")),
+    ("example", include_str!("templates/example.hbs")),
+    ("introduction", include_str!("templates/introduction.hbs")),
+    ("index", include_str!("templates/index.hbs")),
+    ("index-node", include_str!("templates/index-node.hbs")),
+    ("graph", include_str!("templates/graph.hbs")),
+    ("global-attributes", include_str!("templates/global-attributes.hbs")),
+];
+
+
+/// Serializable view of a single tag, as seen by templates.
+#[derive(Debug, Default, Serialize)]
+pub struct TagView {
+    pub namespace: String,
+    pub name: String,
+    /// Link target of this tag's section.
+    pub anchor: String,
+    /// Markdown heading prefix for the configured level, e.g. `##`.
+    pub heading: String,
+    /// Explicit anchor style requested: `implicit`, `attribute` or `html`.
+    pub anchor_style: String,
+    pub level: i32,
+    /// Resolved attribute layout: `list`, `table` or `footnotes`.
+    pub attribute_layout: String,
+    pub description: String,
+    pub deprecated: bool,
+    /// Whether the tag may be nested within itself.
+    pub recursive: bool,
+    /// Whether the tag only serves as a base for others.
+    pub r#abstract: bool,
+    pub attributes: Vec<AttributeView>,
+    /// Link target of the global attributes section, if the tag accepts any of them.
+    pub global_attributes: Option<String>,
+    pub value: Option<String>,
+    pub children: Vec<LinkView>,
+    pub parents: Vec<LinkView>,
+    pub examples: Vec<ExampleView>,
+}
+
+/// Serializable view of an attribute.
+#[derive(Debug, Default, Serialize)]
+pub struct AttributeView {
+    pub name: String,
+    pub brief: String,
+    pub description: Option<String>,
+    pub expected: Option<String>,
+    pub default: Option<String>,
+    pub optional: bool,
+    pub deprecated: bool,
+    /// Base tag declaring the attribute, if it's inherited.
+    pub inherited_from: Option<LinkView>,
+    /// Comma-separated `optional` / `deprecated` markers and inheritance note, if any.
+    pub modifiers: Option<String>,
+    /// Table cells for the `table` and `footnotes` layouts.
+    pub cells: AttributeCellsView,
+}
+
+/// Table cells of an attribute, already escaped to fit a Markdown table row.
+#[derive(Debug, Default, Serialize)]
+pub struct AttributeCellsView {
+    /// Comma-separated `deprecated` marker and inheritance note, if any.
+    pub modifiers: Option<String>,
+    /// `yes` or `no`.
+    pub required: String,
+    pub expected: String,
+    pub default: String,
+    /// Brief description, followed by the long one or a footnote reference depending on the layout.
+    pub description: String,
+    /// Footnote holding the long description, in the `footnotes` layout.
+    pub footnote: Option<FootnoteView>,
+}
+
+/// Serializable view of a footnote definition.
+#[derive(Debug, Default, Serialize)]
+pub struct FootnoteView {
+    pub label: String,
+    /// Footnote text, with continuation lines indented.
+    pub text: String,
+}
+
+/// Serializable view of a child or parent tag reference.
+#[derive(Debug, Default, Serialize)]
+pub struct LinkView {
+    pub namespace: String,
+    pub name: String,
+    /// Link target, missing for unresolved references.
+    pub anchor: Option<String>,
+    pub optional: bool,
+    pub repeated: bool,
+    /// Comma-separated `optional` / `repeated` markers, if any.
+    pub modifiers: Option<String>,
+}
+
+/// Serializable view of an example.
+#[derive(Debug, Default, Serialize)]
+pub struct ExampleView {
+    pub title: String,
+    pub code: String,
+}
+
+
+/// Serializable view of the tag index, as seen by templates.
+#[derive(Debug, Serialize)]
+pub struct IndexView<'a> {
+    /// Markdown heading prefix for the configured level, e.g. `##`.
+    pub heading: String,
+    pub level: i32,
+    #[serde(flatten)]
+    pub index: &'a Index,
+}
+
+/// Serializable view of the document introduction, as seen by templates.
+#[derive(Debug, Serialize)]
+pub struct IntroductionView<'a> {
+    /// Markdown heading prefix for the configured level, e.g. `##`.
+    pub heading: String,
+    pub level: i32,
+    #[serde(flatten)]
+    pub introduction: &'a Introduction,
+}
+
+/// Serializable view of the global attributes section, as seen by templates.
+#[derive(Debug, Serialize)]
+pub struct GlobalAttributesView {
+    /// Link target of the section.
+    pub anchor: String,
+    /// Markdown heading prefix for the configured level, e.g. `##`.
+    pub heading: String,
+    /// Explicit anchor style requested: `implicit`, `attribute` or `html`.
+    pub anchor_style: String,
+    pub level: i32,
+    /// Resolved attribute layout: `list`, `table` or `footnotes`.
+    pub attribute_layout: String,
+    pub attributes: Vec<AttributeView>,
+}
+
+/// Serializable view of the hierarchy graph, as seen by templates.
+#[derive(Debug, Serialize)]
+pub struct GraphView {
+    /// Graph language: `mermaid` or `dot`.
+    pub format: String,
+    pub code: String,
+}
+
+
+/// [`Renderer`] producing output from Handlebars templates, one `tag` template render per tag.
+pub struct TemplateRenderer<'a> {
+    options: &'a GeneratorOptions,
+    writer: &'a mut dyn io::Write,
+    registry: Handlebars<'static>,
+    current: TagView,
+    example_title: String,
+    layout: AttributeLayout,
+}
+
+impl<'a> TemplateRenderer<'a> {
+    /// Create a renderer writing into `writer`, with built-in templates
+    /// overridden by `<name>.hbs` files from the `templates` directory.
+    pub fn new(options: &'a GeneratorOptions, writer: &'a mut dyn io::Write,
+               templates: Option<&Path>) -> GeneratorResult<Self>
+    {
+        let mut registry = Handlebars::new();
+        registry.register_escape_fn(handlebars::no_escape);
+
+        for (name, default) in DEFAULT_TEMPLATES {
+            let custom = match templates.map(|dir| dir.join(format!("{}.hbs", name))) {
+                Some(path) if path.is_file() => {
+                    log::trace!("using custom template {}", path.to_string_lossy());
+                    Some(fs::read_to_string(&path).map_err(|inner| GeneratorError::InternalInputOutput {
+                        inner,
+                        description: Some(format!("failed to read template {}", path.to_string_lossy())),
+                    })?)
+                },
+                _ => None,
+            };
+
+            let source = custom.as_deref().unwrap_or(default);
+            registry.register_template_string(name, source)
+                .map_err(|inner| GeneratorError::BadTemplate { name: name.into(), inner: Box::new(inner) })?;
+        }
+
+        Ok(TemplateRenderer {
+            options,
+            writer,
+            registry,
+            current: TagView::default(),
+            example_title: String::new(),
+            layout: options.attribute_layout.unwrap_or_default(),
+        })
+    }
+
+    /// Build the view of an attribute described in the section with the given `anchor`.
+    fn attribute_view(&self, anchor: &str, attr: &model::Attribute, source: AttributeSource<'_>) -> AttributeView {
+        let inherited_from = match source {
+            AttributeSource::Declared => None,
+            AttributeSource::Inherited { link, namespace, name } => Some(LinkView {
+                namespace: namespace.into(),
+                name: name.into(),
+                anchor: link.map(Into::into),
+                ..Default::default()
+            }),
+        };
+
+        let mut modifiers = vec![];
+        if attr.is_optional { modifiers.push(String::from("optional")); }
+        if attr.is_deprecated { modifiers.push(String::from("deprecated")); }
+        match &inherited_from {
+            Some(LinkView { namespace, name, anchor: Some(anchor), .. }) =>
+                modifiers.push(format!("inherited from [`{}:{}`](#{})", namespace, name, anchor)),
+            Some(LinkView { namespace, name, anchor: None, .. }) =>
+                modifiers.push(format!("inherited from `{}:{}`", namespace, name)),
+            None => (),
+        };
+
+        let cell_modifiers = modifiers.iter()
+            .filter(|modifier| modifier.as_str() != "optional")
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut cells = AttributeCellsView {
+            modifiers: (!cell_modifiers.is_empty()).then(|| table_cell(&cell_modifiers.join(", "))),
+            required: if attr.is_optional { "no" } else { "yes" }.into(),
+            expected: attr.expected_value.as_deref().map(table_cell).unwrap_or_default(),
+            default: attr.default_value.as_deref().map(table_cell).unwrap_or_default(),
+            description: table_cell(&attr.short_description),
+            footnote: None,
+        };
+        if let Some(desc) = &attr.long_description {
+            match self.layout {
+                AttributeLayout::Footnotes => {
+                    let label = format!("{}-{}", anchor, attr.name.to_lowercase());
+                    cells.description.push_str(&format!(" [^{}]", label));
+                    cells.footnote = Some(FootnoteView { label, text: footnote_text(desc, "\n") });
+                },
+                _ => {
+                    cells.description.push_str("<br><br>");
+                    cells.description.push_str(&table_cell(desc));
+                },
+            }
+        }
+
+        AttributeView {
+            name: attr.name.to_string(),
+            brief: attr.short_description.to_string(),
+            description: attr.long_description.clone(),
+            expected: attr.expected_value.as_ref().map(ToString::to_string),
+            default: attr.default_value.as_ref().map(ToString::to_string),
+            optional: attr.is_optional,
+            deprecated: attr.is_deprecated,
+            inherited_from,
+            modifiers: (!modifiers.is_empty()).then(|| modifiers.join(", ")),
+            cells,
+        }
+    }
+
+    fn render(&mut self, name: &str, view: &impl Serialize) -> GeneratorResult<()> {
+        let rendered = self.registry.render(name, view)
+            .map_err(|inner| GeneratorError::InternalTemplate { inner: Box::new(inner), description: None })?;
+
+        match self.options.crlf {
+            true => write!(self.writer, "{}", rendered.replace('\n', "\r\n"))?,
+            false => write!(self.writer, "{}", rendered)?,
+        };
+        Ok(())
+    }
+}
+
+impl<'a> Renderer for TemplateRenderer<'a> {
+    fn document_begin(&mut self, root: &model::TagList) -> GeneratorResult<()> {
+        self.layout = self.options.attribute_layout_for(root);
+        Ok(())
+    }
+
+    fn tag_begin(&mut self, anchor: &str, namespace: &str, tag: &model::Tag) -> GeneratorResult<()> {
+        self.current = TagView {
+            namespace: namespace.into(),
+            name: tag.name.to_string(),
+            anchor: anchor.into(),
+            heading: self.options.level.get_prefix().into(),
+            anchor_style: match self.options.anchor_style.for_anchor(anchor, namespace, &tag.name) {
+                AnchorStyle::Implicit => "implicit",
+                AnchorStyle::Attribute => "attribute",
+                AnchorStyle::Html => "html",
+            }.into(),
+            level: self.options.level.get(),
+            attribute_layout: self.layout.name().into(),
+            description: tag.description.clone(),
+            deprecated: tag.is_deprecated,
+            recursive: tag.is_recursive,
+            r#abstract: tag.is_abstract,
+            ..Default::default()
+        };
+        Ok(())
+    }
+
+    fn introduction(&mut self, introduction: &Introduction) -> GeneratorResult<()> {
+        let view = IntroductionView {
+            heading: self.options.level.get_prefix().into(),
+            level: self.options.level.get(),
+            introduction,
+        };
+        self.render("introduction", &view)
+    }
+
+    fn index(&mut self, index: &Index) -> GeneratorResult<()> {
+        let view = IndexView {
+            heading: self.options.level.get_prefix().into(),
+            level: self.options.level.get(),
+            index,
+        };
+        self.render("index", &view)
+    }
+
+    fn graph(&mut self, format: GraphFormat, code: &str) -> GeneratorResult<()> {
+        let view = GraphView {
+            format: match format {
+                GraphFormat::Mermaid => "mermaid",
+                GraphFormat::Dot => "dot",
+            }.into(),
+            code: code.trim_end().into(),
+        };
+        self.render("graph", &view)
+    }
+
+    fn global_attributes(&mut self, anchor: &str, attributes: &[model::Attribute]) -> GeneratorResult<()> {
+        let view = GlobalAttributesView {
+            anchor: anchor.into(),
+            heading: self.options.level.get_prefix().into(),
+            anchor_style: match self.options.anchor_style {
+                AnchorStyle::Implicit => "implicit",
+                AnchorStyle::Attribute => "attribute",
+                AnchorStyle::Html => "html",
+            }.into(),
+            level: self.options.level.get(),
+            attribute_layout: self.layout.name().into(),
+            attributes: attributes.iter()
+                .map(|attr| self.attribute_view(anchor, attr, AttributeSource::Declared))
+                .collect(),
+        };
+        self.render("global-attributes", &view)
+    }
+
+    fn tag_end(&mut self, _tag: &model::Tag) -> GeneratorResult<()> {
+        let current = std::mem::take(&mut self.current);
+        self.render("tag", &current)
+    }
+
+    fn section_begin(&mut self, section: Section, title: &str) -> GeneratorResult<()> {
+        if section == Section::Example {
+            self.example_title = title.into();
+        }
+        Ok(())
+    }
+
+    fn attribute(&mut self, attr: &model::Attribute, source: AttributeSource<'_>) -> GeneratorResult<()> {
+        let view = self.attribute_view(&self.current.anchor, attr, source);
+        self.current.attributes.push(view);
+        Ok(())
+    }
+
+    fn global_attributes_link(&mut self, link: &str) -> GeneratorResult<()> {
+        self.current.global_attributes = Some(link.into());
+        Ok(())
+    }
+
+    fn value(&mut self, text: &str) -> GeneratorResult<()> {
+        self.current.value = Some(text.into());
+        Ok(())
+    }
+
+    fn child(&mut self, link: Option<&str>, namespace: &str, name: &str, optional: bool, repeated: bool) -> GeneratorResult<()> {
+        let mut modifiers = vec![];
+        if optional { modifiers.push("optional"); }
+        if repeated { modifiers.push("repeated"); }
+
+        self.current.children.push(LinkView {
+            namespace: namespace.into(),
+            name: name.into(),
+            anchor: link.map(Into::into),
+            optional,
+            repeated,
+            modifiers: (!modifiers.is_empty()).then(|| modifiers.join(", ")),
+        });
+        Ok(())
+    }
+
+    fn parent(&mut self, link: &str, namespace: &str, name: &str) -> GeneratorResult<()> {
+        self.current.parents.push(LinkView {
+            namespace: namespace.into(),
+            name: name.into(),
+            anchor: Some(link.into()),
+            ..Default::default()
+        });
+        Ok(())
+    }
+
+    fn no_parents(&mut self) -> GeneratorResult<()> {
+        Ok(())
+    }
+
+    fn example(&mut self, code: &str) -> GeneratorResult<()> {
+        self.current.examples.push(ExampleView {
+            title: std::mem::take(&mut self.example_title),
+            code: code.trim_end().into(),
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{load, options, render};
+    use super::super::{AnchorStyle, AttributeLayout, GeneratorOptions, OutputFormat};
+    use crate::graph::GraphFormat;
+
+    #[test]
+    fn built_in_templates_match_the_markdown_renderer() {
+        let root = load(r#"
+schema:
+  version: r1
+  namespace: ex
+  title: Example format
+  description: Documents with *lists*.
+  extensions: [ex]
+  media_type: application/xml
+attribute_groups:
+  - { id: common, attributes: [{ id: id, brief: Identifier. }] }
+global_attributes:
+  - { id: "xml:lang", brief: Language., optional: true }
+tags:
+  - id: base
+    abstract: true
+    description: Base.
+    attributes: [{ id: kind, brief: Kind., expected: "`a` or `b`", default: a, optional: true, description: "Long\n\nparagraphs." }]
+  - id: list
+    extend: [base]
+    category: Containers
+    description: A *list* of items.
+    attribute_groups: [common]
+    attributes: [{ id: style, brief: Style | pipe., optional: true, deprecated: true }]
+    children: [{ ref: item, multiple: true }, { ref: base, optional: true }, { ref: missing, optional: true }]
+    example: <ex:list/>
+  - { id: item, description: Item., value: Text., children: [{ ref: item, optional: true }] }
+"#);
+        for layout in [AttributeLayout::List, AttributeLayout::Table, AttributeLayout::Footnotes] {
+            for anchor_style in [AnchorStyle::Implicit, AnchorStyle::Attribute, AnchorStyle::Html] {
+                let configured = |format| GeneratorOptions {
+                    attribute_layout: Some(layout),
+                    anchor_style,
+                    index: true,
+                    graph: Some(GraphFormat::Mermaid),
+                    synthesize_examples: true,
+                    abstract_tags: true,
+                    ..options(format)
+                };
+                assert_eq!(render(&root, &configured(OutputFormat::Template)), render(&root, &configured(OutputFormat::Markdown)),
+                    "{:?} layout, {:?} anchors", layout, anchor_style);
+            }
+        }
+    }
+}
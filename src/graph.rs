@@ -0,0 +1,254 @@
+use std::fmt::Write;
+use std::str::FromStr;
+use compact_str::CompactString;
+
+use crate::model;
+
+
+/// Languages a hierarchy graph can be written in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Mermaid `graph` definition, as rendered by `mdbook-mermaid`.
+    #[default]
+    Mermaid,
+    /// Graphviz DOT `digraph`.
+    Dot,
+}
+
+impl FromStr for GraphFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "mermaid" => Ok(GraphFormat::Mermaid),
+            "dot" | "graphviz" => Ok(GraphFormat::Dot),
+            other => Err(format!("unknown graph format '{}', expected 'mermaid' or 'dot'", other)),
+        }
+    }
+}
+
+
+/// Node of the hierarchy graph.
+struct Node {
+    id: String,
+    label: String,
+    style: NodeStyle,
+}
+
+/// How a node of the hierarchy graph stands out.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum NodeStyle {
+    Tag,
+    /// Abstract tag, only drawn when referenced as a child.
+    Abstract,
+    /// Child reference which doesn't name any tag.
+    Unresolved,
+}
+
+/// Parent -> child relation of the hierarchy graph.
+struct Edge {
+    from: String,
+    to: String,
+    optional: bool,
+    repeated: bool,
+}
+
+impl Edge {
+    /// Multiplicity label of the child, if it isn't exactly one.
+    fn label(&self) -> Option<&'static str> {
+        match (self.optional, self.repeated) {
+            (false, false) => None,
+            (true, false) => Some("0..1"),
+            (false, true) => Some("1..*"),
+            (true, true) => Some("0..*"),
+        }
+    }
+}
+
+
+/// Draw the parent -> child hierarchy of all tags in `root`.
+///
+/// Optional children get dashed edges, repeatable children get thick edges,
+/// and unresolved children are highlighted as separate nodes. Abstract tags are left out,
+/// unless referenced as children, in which case they're drawn dashed.
+pub fn draw(root: &model::TagList, format: GraphFormat) -> String {
+    let node_id = |id: model::TagId| format!("t{}", id.index() + 1);
+
+    let mut nodes = root.iter()
+        .filter(|tag| !tag.is_abstract)
+        .map(|tag| Node {
+            id: node_id(tag.id),
+            label: format!("{}:{}", root.namespace, tag.name),
+            style: NodeStyle::Tag,
+        })
+        .collect::<Vec<_>>();
+
+    let mut unresolved = Vec::<CompactString>::new();
+    let mut edges = vec![];
+    for tag in root.iter().filter(|tag| !tag.is_abstract) {
+        for child in &tag.children {
+            let to = match &child.reference {
+                model::ChildInternal::Resolved { id } => node_id(*id),
+                model::ChildInternal::Abstract { id } => {
+                    if !nodes.iter().any(|node| node.id == node_id(*id)) {
+                        nodes.push(Node {
+                            id: node_id(*id),
+                            label: format!("{}:{}", root.namespace, root[*id].name),
+                            style: NodeStyle::Abstract,
+                        });
+                    }
+                    node_id(*id)
+                },
+                model::ChildInternal::Unresolved { name } => {
+                    let position = match unresolved.iter().position(|n| n == name) {
+                        Some(position) => position,
+                        None => {
+                            unresolved.push(name.clone());
+                            nodes.push(Node {
+                                id: format!("u{}", unresolved.len()),
+                                label: format!("{}:{}", root.namespace, name),
+                                style: NodeStyle::Unresolved,
+                            });
+                            unresolved.len() - 1
+                        },
+                    };
+                    format!("u{}", position + 1)
+                },
+            };
+
+            edges.push(Edge {
+                from: node_id(tag.id),
+                to,
+                optional: child.is_optional,
+                repeated: child.is_repeatable,
+            });
+        }
+    }
+
+    match format {
+        GraphFormat::Mermaid => draw_mermaid(&nodes, &edges),
+        GraphFormat::Dot => draw_dot(&nodes, &edges),
+    }
+}
+
+fn draw_mermaid(nodes: &[Node], edges: &[Edge]) -> String {
+    let mut output = String::new();
+
+    // Writing into a String never fails.
+    let _ = writeln!(output, "graph TD");
+    for node in nodes {
+        let class = match node.style {
+            NodeStyle::Tag => "",
+            NodeStyle::Abstract => ":::abstract",
+            NodeStyle::Unresolved => ":::unresolved",
+        };
+        let _ = writeln!(output, "  {}[\"{}\"]{}", node.id, node.label.replace('"', "#quot;"), class);
+    }
+    for edge in edges {
+        let arrow = match (edge.optional, edge.repeated) {
+            (true, _) => "-.->",
+            (false, true) => "==>",
+            (false, false) => "-->",
+        };
+        match edge.label() {
+            Some(label) => { let _ = writeln!(output, "  {} {}|\"{}\"| {}", edge.from, arrow, label, edge.to); },
+            None => { let _ = writeln!(output, "  {} {} {}", edge.from, arrow, edge.to); },
+        };
+    }
+    if nodes.iter().any(|node| node.style == NodeStyle::Abstract) {
+        let _ = writeln!(output, "  classDef abstract stroke-dasharray:4 4");
+    }
+    if nodes.iter().any(|node| node.style == NodeStyle::Unresolved) {
+        let _ = writeln!(output, "  classDef unresolved stroke:#c00,stroke-dasharray:4 4,color:#c00");
+    }
+    output
+}
+
+fn draw_dot(nodes: &[Node], edges: &[Edge]) -> String {
+    let mut output = String::new();
+
+    let _ = writeln!(output, "digraph xmldoc {{");
+    let _ = writeln!(output, "  node [shape=box, fontname=\"monospace\"];");
+    for node in nodes {
+        let style = match node.style {
+            NodeStyle::Tag => "",
+            NodeStyle::Abstract => ", style=dashed",
+            NodeStyle::Unresolved => ", style=dashed, color=red, fontcolor=red",
+        };
+        let _ = writeln!(output, "  {} [label=\"{}\"{}];", node.id, node.label.replace('\\', "\\\\").replace('"', "\\\""), style);
+    }
+    for edge in edges {
+        let mut attributes = vec![];
+        if edge.optional { attributes.push(String::from("style=dashed")); }
+        if edge.repeated { attributes.push(String::from("penwidth=2")); }
+        if let Some(label) = edge.label() { attributes.push(format!("label=\"{}\"", label)); }
+
+        match attributes.is_empty() {
+            true => { let _ = writeln!(output, "  {} -> {};", edge.from, edge.to); },
+            false => { let _ = writeln!(output, "  {} -> {} [{}];", edge.from, edge.to, attributes.join(", ")); },
+        };
+    }
+    let _ = writeln!(output, "}}");
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::loader;
+    use crate::schema::SourceFormat;
+
+    fn load(text: &str) -> model::TagList {
+        let (root, _) = SourceFormat::Yaml.parse(text).unwrap();
+        loader::load_from(root).unwrap().model
+    }
+
+    const TAG_LIST: &str = "
+schema: { version: r1, namespace: ex }
+tags:
+  - { id: base, abstract: true, description: Base. }
+  - id: list
+    description: List.
+    children: [{ ref: item, multiple: true }, { ref: base, optional: true }, { ref: missing, optional: true, multiple: true }]
+  - { id: item, description: Item., children: [{ ref: note }, { ref: item, optional: true }, { ref: missing }] }
+  - { id: note, description: Note. }
+";
+
+    #[test]
+    fn mermaid_graph_styles_nodes_and_edges() {
+        assert_eq!(draw(&load(TAG_LIST), GraphFormat::Mermaid), r#"graph TD
+  t2["ex:list"]
+  t3["ex:item"]
+  t4["ex:note"]
+  t1["ex:base"]:::abstract
+  u1["ex:missing"]:::unresolved
+  t2 ==>|"1..*"| t3
+  t2 -.->|"0..1"| t1
+  t2 -.->|"0..*"| u1
+  t3 --> t4
+  t3 -.->|"0..1"| t3
+  t3 --> u1
+  classDef abstract stroke-dasharray:4 4
+  classDef unresolved stroke:#c00,stroke-dasharray:4 4,color:#c00
+"#);
+    }
+
+    #[test]
+    fn dot_graph_styles_nodes_and_edges() {
+        assert_eq!(draw(&load(TAG_LIST), GraphFormat::Dot), r#"digraph xmldoc {
+  node [shape=box, fontname="monospace"];
+  t2 [label="ex:list"];
+  t3 [label="ex:item"];
+  t4 [label="ex:note"];
+  t1 [label="ex:base", style=dashed];
+  u1 [label="ex:missing", style=dashed, color=red, fontcolor=red];
+  t2 -> t3 [penwidth=2, label="1..*"];
+  t2 -> t1 [style=dashed, label="0..1"];
+  t2 -> u1 [style=dashed, penwidth=2, label="0..*"];
+  t3 -> t4;
+  t3 -> t3 [style=dashed, label="0..1"];
+  t3 -> u1;
+}
+"#);
+    }
+}
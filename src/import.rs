@@ -0,0 +1,973 @@
+use std::fmt;
+use compact_str::CompactString;
+use smallvec::SmallVec;
+
+use crate::schema;
+
+
+/// Placeholder written where an importer couldn't find any documentation.
+pub const PLACEHOLDER: &str = "TODO";
+
+
+/// Imported tag list schema with possible warnings.
+pub struct ImportDigest {
+    /// Tag list schema, ready to be serialized.
+    pub root: schema::FileRoot,
+    /// Source constructs which couldn't be represented in the tag list.
+    pub warnings: SmallVec<[String; 4]>,
+}
+
+/// Possible fatal errors produced by importers.
+#[derive(Debug)]
+pub enum ImportError {
+    /// Source document isn't well-formed XML.
+    MalformedXml { inner: roxmltree::Error },
+    /// Source document root element isn't the one the importer expects.
+    UnexpectedRoot { found: CompactString, expected: CompactString },
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::MalformedXml { inner } =>
+                f.write_fmt(format_args!("malformed xml: {}", inner)),
+            ImportError::UnexpectedRoot { found, expected } =>
+                f.write_fmt(format_args!("unexpected root element '{}', expected '{}'", found, expected)),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImportError::MalformedXml { inner } => Some(inner),
+            ImportError::UnexpectedRoot { .. } => None,
+        }
+    }
+}
+
+
+/// Normalize free-form documentation text: lines are trimmed and joined,
+/// while blank lines are kept as paragraph separators.
+fn clean_text(text: &str) -> String {
+    let mut paragraphs: Vec<String> = vec![];
+    let mut current = String::new();
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() {
+            if !current.is_empty() {
+                paragraphs.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(line);
+    }
+
+    if !current.is_empty() {
+        paragraphs.push(current);
+    }
+
+    paragraphs.join("\n\n")
+}
+
+/// Split cleaned documentation into a brief (its first paragraph) and the remainder, if any.
+fn split_brief(text: &str) -> (CompactString, Option<String>) {
+    match text.split_once("\n\n") {
+        Some((brief, rest)) => (brief.into(), Some(rest.into())),
+        None => (text.into(), None),
+    }
+}
+
+
+/// Conversion of W3C XML Schema (`.xsd`) documents into tag lists.
+pub mod xsd {
+    use std::collections::{HashMap, VecDeque};
+    use roxmltree::{Document, Node, NodeId};
+    use smallvec::smallvec;
+    use super::*;
+
+
+    /// Namespace URI of the XML Schema definition language.
+    const XS_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema";
+
+    /// Namespace prefix used when the schema doesn't declare one for its target namespace.
+    const FALLBACK_PREFIX: &str = "ns";
+
+    /// How deep named type references are followed before giving up.
+    const MAX_TYPE_DEPTH: usize = 16;
+
+
+    /// Configuration of [`import_from`].
+    #[derive(Debug, Default)]
+    pub struct XsdOptions {
+        /// Namespace prefix to use instead of the one declared for `targetNamespace`.
+        pub namespace: Option<CompactString>,
+    }
+
+
+    /// Import a tag list schema from the `text` of an XSD document.
+    pub fn import_from(text: &str, options: &XsdOptions) -> Result<ImportDigest, ImportError> {
+        let document = Document::parse(text)
+            .map_err(|inner| ImportError::MalformedXml { inner })?;
+
+        let root = document.root_element();
+        if !is_xs(root, "schema") {
+            return Err(ImportError::UnexpectedRoot {
+                found: root.tag_name().name().into(),
+                expected: CompactString::from("xs:schema"),
+            });
+        }
+
+        let mut importer = Importer::new();
+        let namespace = match &options.namespace {
+            Some(namespace) => namespace.clone(),
+            None => importer.discover_prefix(root),
+        };
+
+        log::trace!("indexing top-level schema components...");
+        importer.index(root);
+
+        log::trace!("importing element declarations...");
+        importer.run(root);
+
+        Ok(ImportDigest {
+            root: schema::FileRoot {
+                schema: schema::Params {
+                    version: CompactString::from(crate::model::VERSION),
+                    namespace,
+                    ..Default::default()
+                },
+                tags: importer.tags,
+                ..Default::default()
+            },
+            warnings: importer.warnings,
+        })
+    }
+
+
+    /// Occurrence bounds of a particle, relative to its parent tag.
+    #[derive(Clone, Copy, Default)]
+    struct Occurs {
+        optional: bool,
+        multiple: bool,
+    }
+
+    impl Occurs {
+        /// Combine the bounds of `node` with those of its enclosing particle.
+        fn of(node: Node, outer: Occurs) -> Option<Occurs> {
+            let min = node.attribute("minOccurs").unwrap_or("1");
+            let max = node.attribute("maxOccurs").unwrap_or("1");
+
+            if max == "0" {
+                return None;
+            }
+
+            Some(Occurs {
+                optional: outer.optional || min.trim() == "0",
+                multiple: outer.multiple || match max.trim() {
+                    "unbounded" => true,
+                    bound => bound.parse::<u64>().map(|b| b > 1).unwrap_or(false),
+                },
+            })
+        }
+    }
+
+    /// Attributes, children and value collected for a single tag.
+    #[derive(Default)]
+    struct Content {
+        attributes: SmallVec<[schema::Attribute; 4]>,
+        children: SmallVec<[schema::Child; 4]>,
+        value: Option<String>,
+    }
+
+    impl Content {
+        fn push_child(&mut self, name: &str, occurs: Occurs) {
+            // The same element may appear in several particles of one content model.
+            match self.children.iter_mut().find(|c| c.r#ref == name) {
+                Some(existing) => {
+                    existing.optional = (existing.optional == Some(true) && occurs.optional).then_some(true);
+                    existing.multiple = Some(true);
+                },
+                None => self.children.push(schema::Child {
+                    r#ref: name.into(),
+                    optional: occurs.optional.then_some(true),
+                    multiple: occurs.multiple.then_some(true),
+                }),
+            }
+        }
+    }
+
+    struct Importer<'a, 'input> {
+        complex_types: HashMap<&'a str, Node<'a, 'input>>,
+        simple_types: HashMap<&'a str, Node<'a, 'input>>,
+        elements: HashMap<&'a str, Node<'a, 'input>>,
+        attributes: HashMap<&'a str, Node<'a, 'input>>,
+        groups: HashMap<&'a str, Node<'a, 'input>>,
+        attribute_groups: HashMap<&'a str, Node<'a, 'input>>,
+        /// Element declarations which already have (or will have) a tag, by name.
+        declared: HashMap<&'a str, NodeId>,
+        pending: VecDeque<Node<'a, 'input>>,
+        tags: Vec<schema::Tag>,
+        warnings: SmallVec<[String; 4]>,
+    }
+
+    impl<'a, 'input> Importer<'a, 'input> {
+        fn new() -> Self {
+            Importer {
+                complex_types: HashMap::new(),
+                simple_types: HashMap::new(),
+                elements: HashMap::new(),
+                attributes: HashMap::new(),
+                groups: HashMap::new(),
+                attribute_groups: HashMap::new(),
+                declared: HashMap::new(),
+                pending: VecDeque::new(),
+                tags: vec![],
+                warnings: smallvec![],
+            }
+        }
+
+        fn discover_prefix(&mut self, root: Node<'a, 'input>) -> CompactString {
+            let prefix = root.attribute("targetNamespace").and_then(|target| {
+                root.namespaces()
+                    .find(|ns| ns.uri() == target && ns.name().is_some())
+                    .and_then(|ns| ns.name())
+            });
+
+            match prefix {
+                Some(prefix) => prefix.into(),
+                None => {
+                    self.warnings.push(format!("no namespace prefix is declared for the target namespace, \
+                        using '{}' (override with --namespace)", FALLBACK_PREFIX));
+                    CompactString::from(FALLBACK_PREFIX)
+                },
+            }
+        }
+
+        fn index(&mut self, root: Node<'a, 'input>) {
+            for node in root.children().filter(|n| n.is_element()) {
+                let name = node.attribute("name");
+                let component = node.tag_name().name();
+                let target = match component {
+                    "complexType" => &mut self.complex_types,
+                    "simpleType" => &mut self.simple_types,
+                    "element" => &mut self.elements,
+                    "attribute" => &mut self.attributes,
+                    "group" => &mut self.groups,
+                    "attributeGroup" => &mut self.attribute_groups,
+                    "import" | "include" | "redefine" | "override" => {
+                        let location = node.attribute("schemaLocation").unwrap_or("?");
+                        self.warnings.push(format!("xs:{} of '{}' is not followed", component, location));
+                        continue;
+                    },
+                    _ => continue,
+                };
+                if let Some(name) = name {
+                    target.insert(name, node);
+                }
+            }
+        }
+
+        fn run(&mut self, root: Node<'a, 'input>) {
+            for node in root.children().filter(|n| is_xs(*n, "element")) {
+                self.enqueue(node);
+            }
+            while let Some(node) = self.pending.pop_front() {
+                let tag = self.import_element(node);
+                self.tags.push(tag);
+            }
+        }
+
+        /// Schedule an element declaration for import, returning its tag name.
+        fn enqueue(&mut self, node: Node<'a, 'input>) -> Option<&'a str> {
+            let name = node.attribute("name")?;
+            match self.declared.get(name) {
+                Some(id) if *id == node.id() => (),
+                Some(_) => self.warnings.push(format!("element '{}' is declared more than once, \
+                    only the first declaration is imported", name)),
+                None => {
+                    self.declared.insert(name, node.id());
+                    self.pending.push_back(node);
+                },
+            }
+            Some(name)
+        }
+
+        fn import_element(&mut self, element: Node<'a, 'input>) -> schema::Tag {
+            let name = element.attribute("name").unwrap_or_default();
+            let mut description = documentation(element);
+            let mut content = Content::default();
+
+            if element.attribute("abstract") == Some("true") {
+                self.warnings.push(format!("element '{}' is abstract, which is not representable", name));
+            }
+            if let Some(group) = element.attribute("substitutionGroup") {
+                self.warnings.push(format!("substitution group '{}' of element '{}' is not representable", group, name));
+            }
+
+            if let Some(type_name) = element.attribute("type") {
+                match self.complex_types.get(local_name(type_name)).copied() {
+                    Some(complex_type) => {
+                        description = description.or_else(|| documentation(complex_type));
+                        self.import_complex_type(complex_type, name, &mut content, 0);
+                    },
+                    None => content.value = Some(format!("Text of type {}.", self.describe_type(type_name, 0))),
+                }
+            } else if let Some(complex_type) = xs_child(element, "complexType") {
+                self.import_complex_type(complex_type, name, &mut content, 0);
+            } else if let Some(simple_type) = xs_child(element, "simpleType") {
+                content.value = Some(format!("Text of type {}.", self.describe_simple_type(simple_type, 0)));
+            } else {
+                self.warnings.push(format!("element '{}' has no declared type, documented as arbitrary text", name));
+                content.value = Some(String::from("Arbitrary content."));
+            }
+
+            if let Some(fixed) = element.attribute("fixed") {
+                content.value = Some(format!("Always `{}`.", fixed));
+            } else if let (Some(default), Some(value)) = (element.attribute("default"), &mut content.value) {
+                value.push_str(&format!(" Defaults to `{}`.", default));
+            }
+
+            schema::Tag {
+                id: name.into(),
+                description: description.unwrap_or_else(|| String::from(PLACEHOLDER)),
+                attributes: (!content.attributes.is_empty()).then_some(content.attributes),
+                children: (!content.children.is_empty()).then_some(content.children),
+                value: content.value,
+                ..Default::default()
+            }
+        }
+
+        fn import_complex_type(&mut self, complex_type: Node<'a, 'input>, tag: &str, content: &mut Content, depth: usize) {
+            if depth > MAX_TYPE_DEPTH {
+                self.warnings.push(format!("type derivation of '{}' is too deep, possibly circular", tag));
+                return;
+            }
+
+            if complex_type.attribute("mixed") == Some("true") {
+                content.value = Some(String::from("Mixed text content."));
+            }
+
+            for node in complex_type.children().filter(|n| n.is_element()) {
+                match node.tag_name().name() {
+                    "simpleContent" | "complexContent" => self.import_derivation(node, tag, content, depth),
+                    _ => self.import_content_item(node, tag, content),
+                }
+            }
+        }
+
+        fn import_derivation(&mut self, node: Node<'a, 'input>, tag: &str, content: &mut Content, depth: usize) {
+            let simple = node.tag_name().name() == "simpleContent";
+            for derivation in node.children().filter(|n| n.is_element()) {
+                let kind = derivation.tag_name().name();
+                if kind == "annotation" {
+                    continue;
+                }
+                if kind == "restriction" && !simple {
+                    self.warnings.push(format!("complex content restriction in '{}' is imported as-is", tag));
+                }
+
+                if let Some(base) = derivation.attribute("base") {
+                    match self.complex_types.get(local_name(base)).copied() {
+                        Some(base_type) if kind == "extension" || simple =>
+                            self.import_complex_type(base_type, tag, content, depth + 1),
+                        Some(_) => (),
+                        None if simple => content.value = Some(format!("Text of type {}.", self.describe_type(base, 0))),
+                        None => (),
+                    }
+                }
+
+                for item in derivation.children().filter(|n| n.is_element()) {
+                    self.import_content_item(item, tag, content);
+                }
+            }
+        }
+
+        fn import_content_item(&mut self, node: Node<'a, 'input>, tag: &str, content: &mut Content) {
+            match node.tag_name().name() {
+                "sequence" | "choice" | "all" | "group" =>
+                    self.import_particle(node, tag, Occurs::default(), content),
+                "attribute" => self.import_attribute(node, tag, content),
+                "attributeGroup" => self.import_attribute_group(node, tag, content),
+                "anyAttribute" =>
+                    self.warnings.push(format!("attribute wildcard in '{}' is not representable", tag)),
+                "annotation" | "assert" | "openContent" => (),
+                // Facets and the like, which belong to simple content restrictions.
+                other if node.parent().map(|p| p.tag_name().name() == "restriction").unwrap_or(false) => {
+                    log::trace!("ignoring facet xs:{} in '{}'", other, tag);
+                },
+                other => self.warnings.push(format!("unsupported construct xs:{} in '{}'", other, tag)),
+            }
+        }
+
+        fn import_particle(&mut self, node: Node<'a, 'input>, tag: &str, outer: Occurs, content: &mut Content) {
+            let Some(mut occurs) = Occurs::of(node, outer) else {
+                return;
+            };
+
+            match node.tag_name().name() {
+                "element" => {
+                    let name = match node.attribute("ref") {
+                        Some(reference) => {
+                            let name = local_name(reference);
+                            match self.elements.get(name).copied() {
+                                Some(declaration) => { self.enqueue(declaration); },
+                                None => self.warnings.push(format!("element reference '{}' in '{}' can't be resolved", reference, tag)),
+                            }
+                            Some(name)
+                        },
+                        None => self.enqueue(node),
+                    };
+                    if let Some(name) = name {
+                        content.push_child(name, occurs);
+                    }
+                },
+                "sequence" | "all" | "choice" => {
+                    let particles = node.children()
+                        .filter(|n| n.is_element() && n.tag_name().name() != "annotation")
+                        .collect::<SmallVec<[Node; 8]>>();
+                    if node.tag_name().name() == "choice" && particles.len() > 1 {
+                        self.warnings.push(format!("choice in '{}' is documented as a set of optional children", tag));
+                        occurs.optional = true;
+                    }
+                    for particle in particles {
+                        self.import_particle(particle, tag, occurs, content);
+                    }
+                },
+                "group" => {
+                    let group = node.attribute("ref")
+                        .and_then(|reference| self.groups.get(local_name(reference)).copied())
+                        .or_else(|| node.attribute("name").map(|_| node));
+                    match group {
+                        Some(group) => {
+                            for particle in group.children().filter(|n| n.is_element() && n.tag_name().name() != "annotation") {
+                                self.import_particle(particle, tag, occurs, content);
+                            }
+                        },
+                        None => self.warnings.push(format!("model group reference in '{}' can't be resolved", tag)),
+                    }
+                },
+                "any" => self.warnings.push(format!("element wildcard in '{}' is not representable", tag)),
+                "annotation" => (),
+                other => self.warnings.push(format!("unsupported particle xs:{} in '{}'", other, tag)),
+            }
+        }
+
+        fn import_attribute(&mut self, node: Node<'a, 'input>, tag: &str, content: &mut Content) {
+            let usage = node.attribute("use").unwrap_or("optional");
+            if usage == "prohibited" {
+                return;
+            }
+
+            let (name, declaration) = match node.attribute("ref") {
+                Some(reference) => {
+                    let declaration = self.attributes.get(local_name(reference)).copied();
+                    if declaration.is_none() && !reference.starts_with("xml:") {
+                        self.warnings.push(format!("attribute reference '{}' in '{}' can't be resolved", reference, tag));
+                    }
+                    match declaration {
+                        Some(declaration) => (local_name(reference), declaration),
+                        None => (reference, node),
+                    }
+                },
+                None => match node.attribute("name") {
+                    Some(name) => (name, node),
+                    None => {
+                        self.warnings.push(format!("attribute without a name in '{}' is skipped", tag));
+                        return;
+                    },
+                },
+            };
+
+            let text = documentation(node).or_else(|| documentation(declaration));
+            let (brief, description) = match text {
+                Some(text) => split_brief(&text),
+                None => (CompactString::from(PLACEHOLDER), None),
+            };
+
+            let expected = match (declaration.attribute("type"), xs_child(declaration, "simpleType")) {
+                (Some(type_name), _) => Some(self.describe_type(type_name, 0)),
+                (None, Some(simple_type)) => Some(self.describe_simple_type(simple_type, 0)),
+                (None, None) => None,
+            };
+
+            let fixed = node.attribute("fixed").or_else(|| declaration.attribute("fixed"));
+            let expected = match fixed {
+                Some(fixed) => Some(CompactString::from(format!("always `{}`", fixed))),
+                None => expected,
+            };
+
+            let default = node.attribute("default")
+                .or_else(|| declaration.attribute("default"))
+                .map(CompactString::from);
+
+            if content.attributes.iter().any(|a| a.id == name) {
+                self.warnings.push(format!("attribute '{}' is declared more than once in '{}'", name, tag));
+                return;
+            }
+
+            content.attributes.push(schema::Attribute {
+                id: name.into(),
+                brief,
+                description,
+                expected,
+                default,
+                optional: (usage != "required").then_some(true),
+                ..Default::default()
+            });
+        }
+
+        fn import_attribute_group(&mut self, node: Node<'a, 'input>, tag: &str, content: &mut Content) {
+            let group = node.attribute("ref")
+                .and_then(|reference| self.attribute_groups.get(local_name(reference)).copied());
+            match group {
+                Some(group) => {
+                    for item in group.children().filter(|n| n.is_element()) {
+                        self.import_content_item(item, tag, content);
+                    }
+                },
+                None => self.warnings.push(format!("attribute group reference in '{}' can't be resolved", tag)),
+            }
+        }
+
+        /// Describe a type referenced by (possibly prefixed) name as an expected value hint.
+        fn describe_type(&self, type_name: &str, depth: usize) -> CompactString {
+            match self.simple_types.get(local_name(type_name)) {
+                Some(simple_type) if depth <= MAX_TYPE_DEPTH => self.describe_simple_type(*simple_type, depth + 1),
+                _ => CompactString::from(format!("`{}`", type_name)),
+            }
+        }
+
+        fn describe_simple_type(&self, simple_type: Node, depth: usize) -> CompactString {
+            let Some(derivation) = simple_type.children().find(|n| n.is_element() && n.tag_name().name() != "annotation") else {
+                return CompactString::from("text");
+            };
+
+            match derivation.tag_name().name() {
+                "restriction" => {
+                    let values = derivation.children()
+                        .filter(|n| is_xs(*n, "enumeration"))
+                        .filter_map(|n| n.attribute("value"))
+                        .map(|v| format!("`{}`", v))
+                        .collect::<Vec<_>>();
+                    if !values.is_empty() {
+                        return CompactString::from(format!("one of {}", values.join(", ")));
+                    }
+                    match (derivation.attribute("base"), xs_child(derivation, "simpleType")) {
+                        (Some(base), _) => self.describe_type(base, depth),
+                        (None, Some(inner)) => self.describe_simple_type(inner, depth + 1),
+                        (None, None) => CompactString::from("text"),
+                    }
+                },
+                "list" => {
+                    let item = match (derivation.attribute("itemType"), xs_child(derivation, "simpleType")) {
+                        (Some(item), _) => self.describe_type(item, depth),
+                        (None, Some(inner)) => self.describe_simple_type(inner, depth + 1),
+                        (None, None) => CompactString::from("text"),
+                    };
+                    CompactString::from(format!("whitespace-separated list of {}", item))
+                },
+                "union" => {
+                    let mut members = derivation.attribute("memberTypes")
+                        .map(|types| types.split_whitespace().map(|t| self.describe_type(t, depth)).collect::<Vec<_>>())
+                        .unwrap_or_default();
+                    members.extend(derivation.children()
+                        .filter(|n| is_xs(*n, "simpleType"))
+                        .map(|n| self.describe_simple_type(n, depth + 1)));
+                    CompactString::from(format!("any of: {}", members.join("; ")))
+                },
+                _ => CompactString::from("text"),
+            }
+        }
+    }
+
+
+    /// Check whether `node` is an XSD element with the given local name.
+    fn is_xs(node: Node, name: &str) -> bool {
+        node.is_element()
+            && node.tag_name().name() == name
+            && node.tag_name().namespace() == Some(XS_NAMESPACE)
+    }
+
+    /// Find the first XSD child element of `node` with the given local name.
+    fn xs_child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+        node.children().find(|n| is_xs(*n, name))
+    }
+
+    /// Strip the namespace prefix from a qualified name.
+    fn local_name(qualified: &str) -> &str {
+        qualified.rsplit_once(':').map(|(_, local)| local).unwrap_or(qualified)
+    }
+
+    /// Extract the cleaned-up `xs:annotation/xs:documentation` text of a schema component.
+    fn documentation(node: Node) -> Option<String> {
+        let annotation = xs_child(node, "annotation")?;
+        let text = annotation.children()
+            .filter(|n| is_xs(*n, "documentation"))
+            .map(|doc| {
+                let raw = doc.descendants()
+                    .filter(|n| n.is_text())
+                    .filter_map(|n| n.text())
+                    .collect::<String>();
+                clean_text(&raw)
+            })
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        (!text.is_empty()).then_some(text)
+    }
+}
+
+
+/// Inference of skeleton tag lists from sample XML documents.
+pub mod samples {
+    use std::collections::HashMap;
+    use roxmltree::{Document, Node};
+    use smallvec::smallvec;
+    use super::*;
+
+
+    /// Namespace prefix used when the samples don't have a prefixed root element.
+    const FALLBACK_PREFIX: &str = "ns";
+
+
+    /// Configuration of an [`Inferrer`].
+    #[derive(Debug, Default)]
+    pub struct InferOptions {
+        /// Namespace prefix to use instead of the one found on sample root elements.
+        pub namespace: Option<CompactString>,
+    }
+
+    /// Accumulator of observations over any number of sample documents.
+    pub struct Inferrer {
+        namespace: Option<CompactString>,
+        tags: Vec<Observed>,
+        names: HashMap<CompactString, usize>,
+        foreign: Vec<String>,
+        warnings: SmallVec<[String; 4]>,
+    }
+
+    /// Everything seen about a single element name.
+    struct Observed {
+        name: CompactString,
+        /// Number of element instances seen.
+        count: u64,
+        /// Attribute names with the number of instances carrying them.
+        attributes: Vec<(CompactString, u64)>,
+        children: Vec<ObservedChild>,
+        has_text: bool,
+    }
+
+    struct ObservedChild {
+        name: CompactString,
+        /// Number of parent instances which had this child at least once.
+        parents: u64,
+        /// Largest number of occurrences within a single parent instance.
+        most: u64,
+    }
+
+    impl Inferrer {
+        /// Create an empty accumulator.
+        pub fn new(options: &InferOptions) -> Self {
+            Inferrer {
+                namespace: options.namespace.clone(),
+                tags: vec![],
+                names: HashMap::new(),
+                foreign: vec![],
+                warnings: smallvec![],
+            }
+        }
+
+        /// Record every element of a sample document given as `text`.
+        pub fn feed(&mut self, text: &str) -> Result<(), ImportError> {
+            let document = Document::parse(text)
+                .map_err(|inner| ImportError::MalformedXml { inner })?;
+
+            let root = document.root_element();
+            if self.namespace.is_none() {
+                self.namespace = root.tag_name().namespace()
+                    .and_then(|uri| root.lookup_prefix(uri))
+                    .filter(|prefix| !prefix.is_empty())
+                    .map(CompactString::from);
+            }
+
+            let root_namespace = root.tag_name().namespace();
+            self.observe(root, root_namespace);
+            Ok(())
+        }
+
+        /// Produce a tag list schema from everything observed so far.
+        pub fn finish(mut self) -> ImportDigest {
+            let namespace = match self.namespace.take() {
+                Some(namespace) => namespace,
+                None => {
+                    self.warnings.push(format!("sample root elements have no namespace prefix, \
+                        using '{}' (override with --namespace)", FALLBACK_PREFIX));
+                    CompactString::from(FALLBACK_PREFIX)
+                },
+            };
+
+            let tags = self.tags.into_iter()
+                .map(|observed| {
+                    let attributes = observed.attributes.iter()
+                        .map(|(name, count)| schema::Attribute {
+                            id: name.clone(),
+                            brief: CompactString::from(PLACEHOLDER),
+                            optional: (*count < observed.count).then_some(true),
+                            ..Default::default()
+                        })
+                        .collect::<SmallVec<[schema::Attribute; 4]>>();
+
+                    let children = observed.children.iter()
+                        .map(|child| schema::Child {
+                            r#ref: child.name.clone(),
+                            optional: (child.parents < observed.count).then_some(true),
+                            multiple: (child.most > 1).then_some(true),
+                        })
+                        .collect::<SmallVec<[schema::Child; 4]>>();
+
+                    schema::Tag {
+                        id: observed.name,
+                        description: String::from(PLACEHOLDER),
+                        attributes: (!attributes.is_empty()).then_some(attributes),
+                        children: (!children.is_empty()).then_some(children),
+                        value: observed.has_text.then(|| String::from(PLACEHOLDER)),
+                        ..Default::default()
+                    }
+                })
+                .collect();
+
+            ImportDigest {
+                root: schema::FileRoot {
+                    schema: schema::Params {
+                        version: CompactString::from(crate::model::VERSION),
+                        namespace,
+                        ..Default::default()
+                    },
+                    tags,
+                    ..Default::default()
+                },
+                warnings: self.warnings,
+            }
+        }
+
+        fn observe(&mut self, element: Node, root_namespace: Option<&str>) {
+            let name = element.tag_name().name();
+            if let Some(uri) = element.tag_name().namespace() {
+                if Some(uri) != root_namespace && !self.foreign.iter().any(|f| f == uri) {
+                    self.warnings.push(format!("elements from foreign namespace '{}' are merged \
+                        with same-named tags", uri));
+                    self.foreign.push(uri.into());
+                }
+            }
+
+            let index = self.index_of(name);
+
+            let mut attributes: SmallVec<[CompactString; 4]> = smallvec![];
+            for attribute in element.attributes() {
+                let name = match attribute.namespace() {
+                    Some(uri) => match element.lookup_prefix(uri) {
+                        Some(prefix) => CompactString::from(format!("{}:{}", prefix, attribute.name())),
+                        None => CompactString::from(attribute.name()),
+                    },
+                    None => CompactString::from(attribute.name()),
+                };
+                attributes.push(name);
+            }
+
+            let mut children: Vec<(CompactString, u64)> = vec![];
+            let mut has_text = false;
+            for node in element.children() {
+                if node.is_text() {
+                    has_text |= node.text().map(|t| !t.trim().is_empty()).unwrap_or(false);
+                } else if node.is_element() {
+                    let child_name = node.tag_name().name();
+                    match children.iter_mut().find(|(n, _)| *n == child_name) {
+                        Some((_, count)) => *count += 1,
+                        None => children.push((child_name.into(), 1)),
+                    }
+                    self.observe(node, root_namespace);
+                }
+            }
+
+            let observed = &mut self.tags[index];
+            observed.count += 1;
+            observed.has_text |= has_text;
+
+            for attribute in attributes {
+                match observed.attributes.iter_mut().find(|(n, _)| *n == attribute) {
+                    Some((_, count)) => *count += 1,
+                    None => observed.attributes.push((attribute, 1)),
+                }
+            }
+
+            for (child_name, count) in children {
+                match observed.children.iter_mut().find(|c| c.name == child_name) {
+                    Some(child) => {
+                        child.parents += 1;
+                        child.most = child.most.max(count);
+                    },
+                    None => observed.children.push(ObservedChild { name: child_name, parents: 1, most: count }),
+                }
+            }
+        }
+
+        fn index_of(&mut self, name: &str) -> usize {
+            if let Some(index) = self.names.get(name) {
+                return *index;
+            }
+
+            let index = self.tags.len();
+            self.tags.push(Observed {
+                name: name.into(),
+                count: 0,
+                attributes: vec![],
+                children: vec![],
+                has_text: false,
+            });
+            self.names.insert(name.into(), index);
+            index
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::loader;
+    use crate::schema::SourceFormat;
+
+    /// Write an imported tag list and load it back, as `import` followed by `check` would.
+    fn round_trip(digest: ImportDigest) -> loader::LoadDigest {
+        let text = SourceFormat::Yaml.write(&digest.root).expect("imported tag list should serialize");
+        let (root, unknown) = SourceFormat::Yaml.parse(&text).expect("imported tag list should parse");
+        assert!(unknown.is_empty());
+        loader::load_from(root).expect("imported tag list should load")
+    }
+
+    #[test]
+    fn xsd_import_with_xml_lang_loads() {
+        let text = r#"<?xml version="1.0"?>
+            <xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"
+                       xmlns:doc="urn:example:doc" targetNamespace="urn:example:doc">
+                <xs:element name="note">
+                    <xs:complexType>
+                        <xs:attribute ref="xml:lang"/>
+                        <xs:attribute name="id" type="xs:ID" use="required"/>
+                    </xs:complexType>
+                </xs:element>
+            </xs:schema>"#;
+        let digest = xsd::import_from(text, &xsd::XsdOptions::default()).unwrap();
+
+        let model = round_trip(digest).model;
+        let note = &model[model.names["note"]];
+        assert!(note.attributes.iter().any(|attr| attr.name == "xml:lang"));
+    }
+
+    #[test]
+    fn inferred_xml_lang_loads() {
+        let mut inferrer = samples::Inferrer::new(&samples::InferOptions::default());
+        inferrer.feed(r#"<doc:note xmlns:doc="urn:example:doc" xml:lang="en">Hello</doc:note>"#).unwrap();
+
+        let model = round_trip(inferrer.finish()).model;
+        let note = &model[model.names["note"]];
+        assert!(note.attributes.iter().any(|attr| attr.name == "xml:lang"));
+    }
+
+    /// Children of tag `name` as `(ref, optional, multiple)`.
+    fn children_of(root: &schema::FileRoot, name: &str) -> Vec<(String, bool, bool)> {
+        let tag = root.tags.iter().find(|tag| tag.id == name).unwrap();
+        tag.children.iter().flatten()
+            .map(|child| (child.r#ref.to_string(), child.optional == Some(true), child.multiple == Some(true)))
+            .collect()
+    }
+
+    fn child(name: &str, optional: bool, multiple: bool) -> (String, bool, bool) {
+        (name.to_string(), optional, multiple)
+    }
+
+    #[test]
+    fn xsd_particles_map_to_child_bounds() {
+        let text = r#"<?xml version="1.0"?>
+            <xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"
+                       xmlns:doc="urn:example:doc" targetNamespace="urn:example:doc">
+                <xs:group name="meta">
+                    <xs:sequence>
+                        <xs:element name="author" type="xs:string" maxOccurs="2"/>
+                    </xs:sequence>
+                </xs:group>
+                <xs:element name="title" type="xs:string"/>
+                <xs:element name="doc">
+                    <xs:complexType>
+                        <xs:sequence>
+                            <xs:element ref="doc:title"/>
+                            <xs:element name="section" type="xs:string" minOccurs="0" maxOccurs="unbounded"/>
+                            <xs:choice>
+                                <xs:element name="a" type="xs:string"/>
+                                <xs:element name="b" type="xs:string"/>
+                            </xs:choice>
+                            <xs:group ref="doc:meta" minOccurs="0"/>
+                            <xs:element name="gone" type="xs:string" maxOccurs="0"/>
+                            <xs:element ref="doc:title" minOccurs="0"/>
+                            <xs:any/>
+                        </xs:sequence>
+                    </xs:complexType>
+                </xs:element>
+            </xs:schema>"#;
+        let digest = xsd::import_from(text, &xsd::XsdOptions::default()).unwrap();
+
+        assert_eq!(digest.root.schema.namespace, "doc");
+        assert_eq!(children_of(&digest.root, "doc"), [
+            child("title", false, true),
+            child("section", true, true),
+            child("a", true, false),
+            child("b", true, false),
+            child("author", true, true),
+        ]);
+        assert_eq!(digest.warnings.as_slice(), [
+            "choice in 'doc' is documented as a set of optional children",
+            "element wildcard in 'doc' is not representable",
+        ]);
+        assert!(digest.root.tags.iter().all(|tag| tag.id != "gone"));
+        round_trip(digest);
+    }
+
+    #[test]
+    fn inferred_bounds_follow_the_samples() {
+        let mut inferrer = samples::Inferrer::new(&samples::InferOptions::default());
+        inferrer.feed(r#"<d:doc xmlns:d="urn:x" id="1"><d:item/><d:item/><d:note>Hi</d:note></d:doc>"#).unwrap();
+        inferrer.feed(r#"<d:doc xmlns:d="urn:x"><d:item kind="a"/></d:doc>"#).unwrap();
+        let digest = inferrer.finish();
+
+        assert!(digest.warnings.is_empty(), "{:?}", digest.warnings);
+        assert_eq!(digest.root.schema.namespace, "d");
+        assert_eq!(children_of(&digest.root, "doc"), [child("item", false, true), child("note", true, false)]);
+
+        let model = round_trip(digest).model;
+        let optional = |tag: &str, attr: &str| {
+            let tag = &model[model.names[tag]];
+            tag.attributes.iter().find(|a| a.name == attr).unwrap().is_optional
+        };
+        assert!(optional("doc", "id"));
+        assert!(optional("item", "kind"));
+        assert!(model[model.names["note"]].value.is_some());
+        assert!(model[model.names["item"]].value.is_none());
+    }
+
+    #[test]
+    fn inferring_warns_about_missing_prefixes_and_foreign_namespaces() {
+        let mut inferrer = samples::Inferrer::new(&samples::InferOptions::default());
+        inferrer.feed(r#"<doc xmlns="urn:x"><f:item xmlns:f="urn:foreign"/><f:item xmlns:f="urn:foreign"/></doc>"#).unwrap();
+        let digest = inferrer.finish();
+
+        assert_eq!(digest.warnings.as_slice(), [
+            "elements from foreign namespace 'urn:foreign' are merged with same-named tags",
+            "sample root elements have no namespace prefix, using 'ns' (override with --namespace)",
+        ]);
+    }
+}
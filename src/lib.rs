@@ -0,0 +1,96 @@
+//! # mdbook-xmldoc
+//!
+//! Library behind the `mdbook-xmldoc` utility, which serves both as a standalone
+//! tool and an `mdBook` preprocessor for generating simplistic static XML document
+//! reference in an opinionated markdown format.
+//!
+//! Tag list files are deserialized into [`schema::FileRoot`] (see [`schema::SourceFormat`]),
+//! loaded and checked into a [`model::TagList`] by [`model::loader`], whose warnings are
+//! the lints of the tag list, and rendered by [`generator::generate`] or a custom
+//! [`generator::Renderer`]. [`preprocessor::XmlDocPreprocessor`] ties these together for
+//! `mdBook`, while [`import`] produces tag lists from XML Schemas and sample documents.
+
+pub mod example;
+pub mod generator;
+pub mod graph;
+pub mod import;
+pub mod model;
+pub mod names;
+pub mod preprocessor;
+pub mod schema;
+pub mod suggest;
+
+use std::fmt;
+
+
+/// Any error produced by this library.
+#[derive(Debug)]
+pub enum Error {
+    /// A tag list couldn't be loaded.
+    Load(model::loader::LoadError),
+    /// A document couldn't be generated.
+    Generate(generator::GeneratorError),
+    /// A tag list couldn't be imported.
+    Import(import::ImportError),
+    /// A tag list couldn't be serialized.
+    Format(schema::FormatError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Load(inner) => fmt::Display::fmt(inner, f),
+            Error::Generate(inner) => fmt::Display::fmt(inner, f),
+            Error::Import(inner) => fmt::Display::fmt(inner, f),
+            Error::Format(inner) => fmt::Display::fmt(inner, f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Load(inner) => inner.source(),
+            Error::Generate(inner) => inner.source(),
+            Error::Import(inner) => inner.source(),
+            Error::Format(inner) => inner.source(),
+        }
+    }
+}
+
+impl From<model::loader::LoadError> for Error {
+    fn from(inner: model::loader::LoadError) -> Self {
+        Error::Load(inner)
+    }
+}
+
+impl From<generator::GeneratorError> for Error {
+    fn from(inner: generator::GeneratorError) -> Self {
+        Error::Generate(inner)
+    }
+}
+
+impl From<import::ImportError> for Error {
+    fn from(inner: import::ImportError) -> Self {
+        Error::Import(inner)
+    }
+}
+
+impl From<schema::FormatError> for Error {
+    fn from(inner: schema::FormatError) -> Self {
+        Error::Format(inner)
+    }
+}
+
+
+/// Load the tag list file at `path` and generate a document from it, returning
+/// the document along with the tag list warnings.
+pub fn generate_path(path: &std::path::Path, load_options: &model::loader::LoadOptions,
+                     options: &generator::GeneratorOptions) -> Result<(String, Vec<String>), Error>
+{
+    let model::loader::LoadDigest { model, warnings } = model::loader::load_path_with(path, load_options)?;
+
+    let mut output = Vec::new();
+    generator::generate(&model, options, &mut output)?;
+    Ok((String::from_utf8_lossy(&output).into_owned(), warnings.into_vec()))
+}
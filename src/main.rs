@@ -1,35 +1,40 @@
-#![allow(dead_code)]
-
 //! # mdbook-xmldoc
 //!
-//! This binary crate provides a joint utility which serves both as a standalone
+//! Command-line wrapper over the `mdbook_xmldoc` library, which serves both as a standalone
 //! tool and an `mdBook` preprocessor for generating simplistic static XML document
 //! reference in an opinionated markdown format.
 
-mod generator;
-mod model;
-mod schema;
-
 use std::fs::File;
-use std::io;
+use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::process;
 
 use clap::{Parser, Subcommand};
+use mdbook::preprocess::{CmdPreprocessor, Preprocessor};
 
-use crate::model::loader;
+use mdbook_xmldoc::{example, generator, graph, import, preprocessor, schema};
+use mdbook_xmldoc::model::{self, loader};
 
 
 #[derive(Debug, Parser)]
 #[command(author, version, about)]
 #[command(propagate_version = true)]
 struct Cli {
-    /// Provide additional diagnostics. DISABLE FOR MDBOOK!
+    /// Provide additional diagnostics, overriding MDBOOK_LOG and RUST_LOG.
     #[arg(long)]
     verbose: bool,
     /// Disable colored logging, useful when piping output to files.
     #[arg(long)]
     no_colors: bool,
+    /// Tag list format: "yaml", "json" or "toml" (default: guessed from the file extension).
+    #[arg(long, global = true)]
+    input_format: Option<schema::SourceFormat>,
+    /// Treat tag list keys unknown to the schema as errors rather than warnings.
+    #[arg(long, global = true)]
+    strict: bool,
+    /// Allow tags whose anchors collide (e.g. "Item" and "item"), numbering them in source order.
+    #[arg(long, global = true)]
+    allow_anchor_collisions: bool,
 
     #[command(subcommand)]
     command: Option<Command>,
@@ -40,14 +45,86 @@ enum Command {
     /// Checks that a given file is a valid .yml tag list.
     Check {
         /// Path to checked .yml file.
-        file: PathBuf
+        file: PathBuf,
+        /// Rewrite unresolved child references which have a single likely target.
+        #[arg(long)]
+        fix: bool,
     },
-    /// Generates a pure markdown file from the given file.
+    /// Generates a pure markdown (or html, or json) file from the given file.
     Generate {
         /// Path to input .yml file.
         file: PathBuf,
         /// Path to output file, or "(stdout)".
         output: PathBuf,
+        /// Output format: "markdown", "html", "template" or "json".
+        #[arg(long, default_value = "markdown")]
+        format: generator::OutputFormat,
+        /// Heading anchors: "implicit", "attribute" ({#id}) or "html" (<a id>).
+        #[arg(long, default_value = "implicit")]
+        anchors: generator::AnchorStyle,
+        /// Attribute layout: "list", "table" or "footnotes" (default: as the tag list prefers).
+        #[arg(long)]
+        attribute_layout: Option<generator::AttributeLayout>,
+        /// Directory with .hbs templates overriding the built-in ones, implies "--format template".
+        #[arg(long)]
+        templates: Option<PathBuf>,
+        /// Synthesize examples for tags which don't provide one.
+        #[arg(long)]
+        synthesize_examples: bool,
+        /// Maximum nesting depth of synthesized examples.
+        #[arg(long, default_value_t = example::DEFAULT_DEPTH, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+        example_depth: usize,
+        /// Start the document with an index of all tags.
+        #[arg(long)]
+        index: bool,
+        /// Embed a hierarchy graph: "mermaid" or "dot".
+        #[arg(long)]
+        graph: Option<graph::GraphFormat>,
+        /// Describe abstract base tags too.
+        #[arg(long)]
+        abstract_tags: bool,
+        /// Tag order: "source", "alphabetical" or "hierarchy".
+        #[arg(long, default_value = "source")]
+        order: generator::TagOrder,
+    },
+    /// Draws the tag hierarchy of the given file as a Mermaid or DOT graph.
+    Graph {
+        /// Path to input .yml file.
+        file: PathBuf,
+        /// Path to output file, or "(stdout)".
+        #[arg(default_value = "(stdout)")]
+        output: PathBuf,
+        /// Graph format: "mermaid" or "dot".
+        #[arg(long, default_value = "mermaid")]
+        format: graph::GraphFormat,
+    },
+    /// Converts a tag list between the yaml, json and toml formats.
+    Convert {
+        /// Path to input tag list.
+        file: PathBuf,
+        /// Path to output tag list, or "(stdout)".
+        #[arg(default_value = "(stdout)")]
+        output: PathBuf,
+        /// Output format: "yaml", "json" or "toml" (default: guessed from the output extension, or yaml).
+        #[arg(long)]
+        to: Option<schema::SourceFormat>,
+    },
+    /// Converts a schema in another format into a .yml tag list.
+    Import {
+        #[command(subcommand)]
+        source: ImportSource,
+    },
+    /// Infers a skeleton .yml tag list from sample XML documents.
+    Infer {
+        /// Paths to sample .xml files.
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Path to output .yml file, or "(stdout)".
+        #[arg(long, short, default_value = "(stdout)")]
+        output: PathBuf,
+        /// Namespace prefix to use instead of the one found on the root elements.
+        #[arg(long)]
+        namespace: Option<String>,
     },
     /// (mdBook) Checks if an mdBook renderer is supported.
     Supports {
@@ -56,15 +133,33 @@ enum Command {
     },
 }
 
+#[derive(Debug, Subcommand)]
+enum ImportSource {
+    /// Imports element and attribute declarations from an XML Schema.
+    Xsd {
+        /// Path to input .xsd file.
+        file: PathBuf,
+        /// Path to output .yml file, or "(stdout)".
+        #[arg(default_value = "(stdout)")]
+        output: PathBuf,
+        /// Namespace prefix to use instead of the one bound to the target namespace.
+        #[arg(long)]
+        namespace: Option<String>,
+    },
+}
+
 
 fn main() {
     let cli_args = Cli::parse();
-    let log_dispatch = {
-        let get_filter = |verbose| match verbose {
-            false => log::LevelFilter::Info,
-            true => log::LevelFilter::Trace,
-        };
+    // Without a subcommand, mdBook reads the preprocessed book from stdout,
+    // so every record has to go to stderr instead.
+    let preprocessing = cli_args.command.is_none();
+    let (log_filter, log_directives) = match cli_args.verbose {
+        true => (Some(log::LevelFilter::Trace), vec![]),
+        false => log_filter_from_env(),
+    };
 
+    let log_dispatch = {
         let get_prefix = |level| match level {
             log::Level::Error => "Error: ",
             log::Level::Warn => "Warning: ",
@@ -73,31 +168,38 @@ fn main() {
             log::Level::Trace => "TRACING: "
         };
 
-        let no_colors = cli_args.no_colors;
+        let no_colors = cli_args.no_colors || preprocessing;
         let colors = fern::colors::ColoredLevelConfig::new()
             .error(fern::colors::Color::Red)
             .warn(fern::colors::Color::Yellow)
             .trace(fern::colors::Color::BrightBlack);
 
         let stdout_dispatch = fern::Dispatch::new()
-            .filter(|metadata| metadata.level() < log::Level::Error)
+            .filter(move |metadata| !preprocessing && metadata.level() < log::Level::Error)
             .chain(io::stdout());
         let stderr_dispatch = fern::Dispatch::new()
-            .filter(|metadata| metadata.level() >= log::Level::Error)
+            .filter(move |metadata| preprocessing || metadata.level() >= log::Level::Error)
             .chain(io::stderr());
 
-        fern::Dispatch::new()
+        let mut dispatch = fern::Dispatch::new()
             .format(move |out, message, record| {
                 let prefix = get_prefix(record.level());
                 if !no_colors {
                     let color = colors.get_color(&record.level());
                     out.finish(format_args!("\x1B[{}m{}{} \x1B[0m",
                         color.to_fg_str(), prefix, message))
+                } else if preprocessing {
+                    out.finish(format_args!("[xmldoc] {}{}", prefix, message))
                 } else {
                     out.finish(format_args!("{}{}", prefix, message))
                 }
             })
-            .level(get_filter(cli_args.verbose))
+            .level(log_filter.unwrap_or(log::LevelFilter::Info));
+        for (target, level) in &log_directives {
+            dispatch = dispatch.level_for(target.clone(), *level);
+        }
+
+        dispatch
             .chain(stdout_dispatch)
             .chain(stderr_dispatch)
     };
@@ -107,12 +209,44 @@ fn main() {
         eprintln!("exiting...");
         process::exit(3);
     }
+    if log_filter.is_none() && log_directives.is_empty() && env_log_spec().is_some() {
+        log::warn!("ignoring MDBOOK_LOG / RUST_LOG, as it has no valid level directives");
+    }
+
+    let load_options = loader::LoadOptions {
+        format: cli_args.input_format,
+        deny_unknown_keys: cli_args.strict,
+        allow_anchor_collisions: cli_args.allow_anchor_collisions,
+    };
 
     let success = match &cli_args.command {
-        Some(Command::Check { file }) =>
-            exec_check(file.as_path()),
-        Some(Command::Generate { file, output }) =>
-            exec_generate(file.as_path(), output.as_path()),
+        Some(Command::Check { file, fix }) =>
+            exec_check(file.as_path(), &load_options, *fix),
+        Some(Command::Generate { file, output, format, anchors, attribute_layout, templates, synthesize_examples, example_depth, index, graph, abstract_tags, order }) => {
+            let options = generator::GeneratorOptions {
+                format: if templates.is_some() { generator::OutputFormat::Template } else { *format },
+                level: generator::HeaderLevel::new(1).unwrap(),
+                crlf: false,
+                synthesize_examples: *synthesize_examples,
+                example_depth: *example_depth,
+                anchor_style: *anchors,
+                attribute_layout: *attribute_layout,
+                templates: templates.clone(),
+                index: *index,
+                graph: *graph,
+                order: *order,
+                abstract_tags: *abstract_tags,
+            };
+            exec_generate(file.as_path(), &load_options, output.as_path(), &options)
+        },
+        Some(Command::Graph { file, output, format }) =>
+            exec_graph(file.as_path(), &load_options, output.as_path(), *format),
+        Some(Command::Convert { file, output, to }) =>
+            exec_convert(file.as_path(), &load_options, output.as_path(), *to),
+        Some(Command::Import { source: ImportSource::Xsd { file, output, namespace } }) =>
+            exec_import_xsd(file.as_path(), output.as_path(), namespace.as_deref()),
+        Some(Command::Infer { files, output, namespace }) =>
+            exec_infer(files, output.as_path(), namespace.as_deref()),
         Some(Command::Supports { renderer }) =>
             exec_supports(renderer),
         None =>
@@ -129,10 +263,10 @@ fn main() {
 }
 
 
-fn exec_check(path: &Path) -> bool {
+fn exec_check(path: &Path, load_options: &loader::LoadOptions, fix: bool) -> bool {
     log::trace!("checking file at {}", path.to_string_lossy());
 
-    if let Some(loader::LoadDigest { warnings, .. }) = internal_load(path) {
+    if let Some(loader::LoadDigest { model, warnings }) = internal_load(path, load_options) {
         for warning in &warnings {
             log::warn!("warning: {}", warning);
         }
@@ -143,47 +277,103 @@ fn exec_check(path: &Path) -> bool {
             _ => log::warn!("file has warning(s): {}", warning_count),
         };
 
-        true
+        match fix {
+            true => internal_fix_references(path, load_options, &model),
+            false => true,
+        }
     } else {
         false
     }
 }
 
-fn exec_generate(path: &Path, output: &Path) -> bool {
-    log::trace!("generating markdown from {} into {}", path.to_string_lossy(), output.to_string_lossy());
+fn internal_fix_references(path: &Path, load_options: &loader::LoadOptions, model: &model::TagList) -> bool {
+    let suggestions = loader::suggest_references(model);
+    let fixes = suggestions.iter()
+        .filter_map(|suggestion| suggestion.unambiguous().map(|target| (suggestion.reference.as_str(), target)))
+        .collect::<Vec<_>>();
+
+    for suggestion in suggestions.iter().filter(|suggestion| suggestion.unambiguous().is_none()) {
+        match suggestion.candidates.is_empty() {
+            true => log::warn!("can't fix reference '{}', no similar tag names", suggestion.reference),
+            false => log::warn!("can't fix reference '{}', {}", suggestion.reference, suggestion),
+        };
+    }
+    if fixes.is_empty() {
+        log::info!("nothing to fix");
+        return true;
+    }
+
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) => {
+            log::error!("failed to read source file '{}' for fixing", path.to_string_lossy());
+            log::error!("reason: {}", error);
+            return false;
+        },
+    };
 
-    if let Some(loader::LoadDigest { model, warnings }) = internal_load(path) {
+    let format = load_options.format.unwrap_or_else(|| schema::SourceFormat::from_path(path));
+    let (text, count) = format.rewrite_references(&text, &fixes);
+    if let Err(error) = std::fs::write(path, text) {
+        log::error!("failed to write fixed source file '{}'", path.to_string_lossy());
+        log::error!("reason: {}", error);
+        return false;
+    }
+
+    for (from, to) in &fixes {
+        log::info!("fixed reference '{}' -> '{}'", from, to);
+    }
+    log::info!("rewrote {} reference(s) in '{}'", count, path.to_string_lossy());
+    true
+}
+
+fn exec_generate(path: &Path, load_options: &loader::LoadOptions, output: &Path,
+                 options: &generator::GeneratorOptions) -> bool
+{
+    log::trace!("generating {:?} from {} into {}", options.format, path.to_string_lossy(), output.to_string_lossy());
+
+    if let Some(loader::LoadDigest { model, warnings }) = internal_load(path, load_options) {
         for warning in &warnings {
             log::warn!("warning: {}", warning);
         }
 
-        let options = generator::GeneratorOptions {
-            level: generator::HeaderLevel::new(1).unwrap(),
-            crlf: false,
+        let Some(mut writer) = internal_create_output(output) else {
+            return false;
         };
 
-        let generator_result = if output.to_string_lossy() == "(stdout)" {
-            log::trace!("selected standard output as the output writer");
-            generator::generate(&model, &options, &mut io::stdout())
-        } else {
-            log::trace!("selected file {} as the output writer", output.to_string_lossy());
-            match File::create(output) {
-                Ok(file) => {
-                    log::trace!("file opened successfully");
-                    let mut writer = io::BufWriter::new(file);
-                    generator::generate(&model, &options, &mut writer)
-                },
-                Err(error) => {
-                    log::error!("failed to create or truncate output file: {}", error);
-                    return false;
-                }
+        let generator_result = generator::generate(&model, options, &mut writer);
+
+        match generator_result {
+            Ok(()) => true,
+            Err(error) => {
+                log::error!("failed to generate {:?}: {}", options.format, error);
+                false
             }
+        }
+    } else {
+        false
+    }
+}
+
+fn exec_graph(path: &Path, load_options: &loader::LoadOptions, output: &Path, format: graph::GraphFormat) -> bool {
+    log::trace!("drawing {:?} graph of {} into {}", format, path.to_string_lossy(), output.to_string_lossy());
+
+    if let Some(loader::LoadDigest { model, warnings }) = internal_load(path, load_options) {
+        for warning in &warnings {
+            log::warn!("warning: {}", warning);
+        }
+
+        let Some(mut writer) = internal_create_output(output) else {
+            return false;
         };
 
-        match generator_result {
+        let result = writer.write_all(graph::draw(&model, format).as_bytes())
+            .and_then(|()| writer.flush());
+
+        match result {
             Ok(()) => true,
             Err(error) => {
-                log::error!("failed to generate markdown: {}", error);
+                log::error!("failed to write graph: {}", error);
                 false
             }
         }
@@ -192,8 +382,90 @@ fn exec_generate(path: &Path, output: &Path) -> bool {
     }
 }
 
+fn exec_convert(path: &Path, load_options: &loader::LoadOptions, output: &Path,
+                output_format: Option<schema::SourceFormat>) -> bool
+{
+    log::trace!("converting {} into {}", path.to_string_lossy(), output.to_string_lossy());
+
+    match internal_read(path, load_options) {
+        Some(loader::ReadDigest { root, warnings }) => {
+            for warning in &warnings {
+                log::warn!("warning: {}", warning);
+            }
+            internal_write_schema(&root, output, output_format)
+        },
+        None => false,
+    }
+}
+
+fn exec_import_xsd(path: &Path, output: &Path, namespace: Option<&str>) -> bool {
+    log::trace!("importing xml schema from {} into {}", path.to_string_lossy(), output.to_string_lossy());
+
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) => {
+            log::error!("failed to read source file '{}'", path.to_string_lossy());
+            log::error!("reason: {}", err);
+            return false;
+        }
+    };
+
+    let options = import::xsd::XsdOptions {
+        namespace: namespace.map(Into::into),
+    };
+
+    match import::xsd::import_from(&text, &options) {
+        Ok(import::ImportDigest { root, warnings }) => {
+            for warning in &warnings {
+                log::warn!("warning: {}", warning);
+            }
+            internal_write_schema(&root, output, None)
+        },
+        Err(error) => {
+            log::error!("failed to import xml schema '{}'", path.to_string_lossy());
+            log::error!("reason: {}", error);
+            false
+        }
+    }
+}
+
+fn exec_infer(paths: &[PathBuf], output: &Path, namespace: Option<&str>) -> bool {
+    log::trace!("inferring tag list from {} sample(s) into {}", paths.len(), output.to_string_lossy());
+
+    let options = import::samples::InferOptions {
+        namespace: namespace.map(Into::into),
+    };
+    let mut inferrer = import::samples::Inferrer::new(&options);
+
+    for path in paths {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) => {
+                log::error!("failed to read sample file '{}'", path.to_string_lossy());
+                log::error!("reason: {}", err);
+                return false;
+            }
+        };
+
+        if let Err(error) = inferrer.feed(&text) {
+            log::error!("failed to scan sample file '{}'", path.to_string_lossy());
+            log::error!("reason: {}", error);
+            return false;
+        }
+
+        log::trace!("scanned sample {}", path.to_string_lossy());
+    }
+
+    let import::ImportDigest { root, warnings } = inferrer.finish();
+    for warning in &warnings {
+        log::warn!("warning: {}", warning);
+    }
+
+    internal_write_schema(&root, output, None)
+}
+
 fn exec_supports(renderer: &str) -> bool {
-    let supports = renderer.trim().to_lowercase() == "html";
+    let supports = preprocessor::XmlDocPreprocessor.supports_renderer(renderer);
     match supports {
         true => {
             log::info!("the given renderer '{}' is supported", renderer);
@@ -207,43 +479,243 @@ fn exec_supports(renderer: &str) -> bool {
 }
 
 fn exec_preprocess() -> bool {
-    // TODO: Implement!
-    true
-}
+    let (ctx, book) = match CmdPreprocessor::parse_input(io::stdin()) {
+        Ok(input) => input,
+        Err(error) => {
+            log::error!("failed to parse mdbook input: {}", error);
+            return false;
+        }
+    };
 
+    if ctx.mdbook_version != mdbook::MDBOOK_VERSION {
+        log::warn!("mdbook-xmldoc was built against mdbook {}, but is called from mdbook {}",
+            mdbook::MDBOOK_VERSION, ctx.mdbook_version);
+    }
 
-fn internal_load(path: &Path) -> Option<loader::LoadDigest> {
-    let mut reader = match File::open(path) {
-        Ok(file) => file,
-        Err(err) => {
-            log::error!("failed to open source file '{}'", path.to_string_lossy());
-            log::error!("reason: {}", err.to_string());
-            return None;
+    let book = match preprocessor::XmlDocPreprocessor.run(&ctx, book) {
+        Ok(book) => book,
+        Err(error) => {
+            log::error!("failed to preprocess book: {}", error);
+            return false;
         }
     };
 
-    log::trace!("input file opened successfully");
+    match serde_json::to_writer(io::stdout(), &book) {
+        Ok(()) => true,
+        Err(error) => {
+            log::error!("failed to write preprocessed book: {}", error);
+            false
+        }
+    }
+}
+
 
-    let root: schema::FileRoot = match serde_yaml::from_reader(&mut reader) {
-        Ok(root) => root,
-        Err(err) => {
-            log::error!("failed to parse tag list from source file '{}'", path.to_string_lossy());
-            log::error!("reason: {}", err.to_string());
-            return None;
+/// Logging filter specification, `MDBOOK_LOG` taking precedence over `RUST_LOG`.
+fn env_log_spec() -> Option<String> {
+    ["MDBOOK_LOG", "RUST_LOG"].into_iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|spec| !spec.trim().is_empty())
+}
+
+/// Parse `env_logger`-style `level` and `target=level` directives from the environment,
+/// see [`parse_log_filter`].
+fn log_filter_from_env() -> (Option<log::LevelFilter>, Vec<(String, log::LevelFilter)>) {
+    parse_log_filter(&env_log_spec().unwrap_or_default())
+}
+
+/// Parse comma-separated `level` and `target=level` directives into the default level (if any)
+/// and per-target levels. Invalid directives are skipped.
+fn parse_log_filter(spec: &str) -> (Option<log::LevelFilter>, Vec<(String, log::LevelFilter)>) {
+    let mut default = None;
+    let mut targets = vec![];
+
+    for directive in spec.split(',').map(str::trim) {
+        match directive.split_once('=') {
+            Some((target, level)) => {
+                // Accept the package name as well as the crate name used for targets.
+                let target = target.trim().replace('-', "_");
+                if let Ok(level) = level.trim().parse::<log::LevelFilter>() {
+                    targets.push((target, level));
+                }
+            },
+            None => match directive.parse::<log::LevelFilter>() {
+                Ok(level) => default = Some(level),
+                // A bare target enables everything for it.
+                Err(_) if !directive.is_empty() => targets.push((directive.replace('-', "_"), log::LevelFilter::Trace)),
+                Err(_) => (),
+            },
         }
-    };
+    }
 
-    log::trace!("schema parsed successfully");
+    (default, targets)
+}
 
-    match loader::load_from(root) {
+fn internal_read(path: &Path, load_options: &loader::LoadOptions) -> Option<loader::ReadDigest> {
+    match loader::read_path_with(path, load_options) {
+        Ok(digest) => Some(digest),
+        Err(error) => {
+            internal_log_load_error(path, error);
+            None
+        }
+    }
+}
+
+fn internal_load(path: &Path, load_options: &loader::LoadOptions) -> Option<loader::LoadDigest> {
+    match loader::load_path_with(path, load_options) {
         Ok(digest) => {
             log::trace!("model loaded successfully");
             Some(digest)
         },
         Err(error) => {
-            log::error!("failed to load model from deserialized schema '{}'", path.to_string_lossy());
-            log::error!("reason: {:?}", error);
+            internal_log_load_error(path, error);
             None
         }
     }
 }
+
+fn internal_log_load_error(path: &Path, error: loader::LoadError) {
+    match error {
+        loader::LoadError::InputOutput { inner } => {
+            log::error!("failed to open source file '{}'", path.to_string_lossy());
+            log::error!("reason: {}", inner);
+        },
+        loader::LoadError::Parse { format, inner } => {
+            match inner.location() {
+                Some((line, column)) => log::error!("failed to parse {} tag list from source file '{}' at line {}, column {}",
+                    format, path.to_string_lossy(), line, column),
+                None => log::error!("failed to parse {} tag list from source file '{}'", format, path.to_string_lossy()),
+            };
+            log::error!("reason: {}", inner.message());
+        },
+        loader::LoadError::UnknownKeys { keys } => {
+            log::error!("tag list '{}' has {} unknown key(s)", path.to_string_lossy(), keys.len());
+            for key in &keys {
+                log::error!("  {}", key);
+            }
+        },
+        loader::LoadError::DuplicateTags { names } => {
+            log::error!("tag list '{}' has {} duplicate tag(s)", path.to_string_lossy(), names.len());
+            for name in &names {
+                log::error!("  {}", name);
+            }
+            log::error!("every tag must have a unique id, merge or rename the duplicates");
+        },
+        loader::LoadError::AnchorCollisions { collisions } => {
+            log::error!("tag list '{}' has {} anchor collision(s)", path.to_string_lossy(), collisions.len());
+            for collision in &collisions {
+                log::error!("  {}", collision);
+            }
+            log::error!("rename the tags, or pass --allow-anchor-collisions to number them in source order");
+        },
+        loader::LoadError::RequiredCycles { cycles } => {
+            log::error!("tag list '{}' has {} cycle(s) of required children", path.to_string_lossy(), cycles.len());
+            for cycle in &cycles {
+                log::error!("  {}", cycle.join(", "));
+            }
+            log::error!("no finite document can contain these tags, make at least one child in each cycle optional");
+        },
+        loader::LoadError::InvalidNames { names } => {
+            log::error!("tag list '{}' has {} invalid name(s)", path.to_string_lossy(), names.len());
+            for name in &names {
+                log::error!("  {}", name);
+            }
+        },
+        error => {
+            log::error!("failed to load model from deserialized schema '{}'", path.to_string_lossy());
+            log::error!("reason: {}", error);
+        }
+    }
+}
+
+fn internal_create_output(output: &Path) -> Option<Box<dyn io::Write>> {
+    if output.to_string_lossy() == "(stdout)" {
+        log::trace!("selected standard output as the output writer");
+        Some(Box::new(io::stdout()))
+    } else {
+        log::trace!("selected file {} as the output writer", output.to_string_lossy());
+        match File::create(output) {
+            Ok(file) => {
+                log::trace!("file opened successfully");
+                Some(Box::new(io::BufWriter::new(file)))
+            },
+            Err(error) => {
+                log::error!("failed to create or truncate output file: {}", error);
+                None
+            }
+        }
+    }
+}
+
+fn internal_write_schema(root: &schema::FileRoot, output: &Path, format: Option<schema::SourceFormat>) -> bool {
+    let format = format.unwrap_or_else(|| schema::SourceFormat::from_path(output));
+    log::trace!("writing tag list as {}", format);
+
+    let text = match format.write(root) {
+        Ok(text) => text,
+        Err(error) => {
+            log::error!("failed to serialize tag list as {}: {}", format, error);
+            return false;
+        }
+    };
+
+    let Some(mut writer) = internal_create_output(output) else {
+        return false;
+    };
+
+    let result = writer.write_all(text.as_bytes())
+        .and_then(|()| writer.flush());
+
+    match result {
+        Ok(()) => true,
+        Err(error) => {
+            log::error!("failed to write tag list: {}", error);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_directives_set_default_and_target_levels() {
+        use log::LevelFilter;
+
+        assert_eq!(parse_log_filter(""), (None, vec![]));
+        assert_eq!(parse_log_filter("debug"), (Some(LevelFilter::Debug), vec![]));
+        assert_eq!(parse_log_filter(" warn , mdbook-xmldoc=trace, mdbook = off "), (Some(LevelFilter::Warn), vec![
+            ("mdbook_xmldoc".to_string(), LevelFilter::Trace),
+            ("mdbook".to_string(), LevelFilter::Off),
+        ]));
+        // A bare target enables everything for it, the last bare level wins, and invalid levels are skipped.
+        assert_eq!(parse_log_filter("info,handlebars,error,mdbook=loud,,"), (Some(LevelFilter::Error), vec![
+            ("handlebars".to_string(), LevelFilter::Trace),
+        ]));
+    }
+
+    #[test]
+    fn fix_leaves_abstract_references_alone() {
+        let text = "\
+schema: { version: r1, namespace: ex }
+tags:
+  - { id: base, abstract: true, description: Base. }
+  - { id: item, description: Item. }
+  - id: list
+    description: List.
+    children: [{ ref: base }, { ref: itme }]
+";
+        let path = std::env::temp_dir().join(format!("xmldoc-fix-{}.yaml", std::process::id()));
+        std::fs::write(&path, text).unwrap();
+
+        let load_options = loader::LoadOptions::default();
+        let digest = loader::load_path_with(&path, &load_options).unwrap();
+        let suggestions = loader::suggest_references(&digest.model);
+        assert_eq!(suggestions.iter().map(|s| s.reference.as_str()).collect::<Vec<_>>(), ["itme"]);
+
+        assert!(internal_fix_references(&path, &load_options, &digest.model));
+        let fixed = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(fixed, text.replace("ref: itme", "ref: item"));
+    }
+}
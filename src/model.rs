@@ -1,7 +1,8 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::ops;
 use compact_str::CompactString;
 use smallvec::SmallVec;
-use uuid::Uuid;
 
 
 /// The latest schema identifier implemented by this version of `mdbook-xmldoc`.
@@ -19,23 +20,115 @@ pub fn is_supported(version: &str) -> bool {
 pub struct TagList {
     /// The XML namespace of all tags in this list.
     pub namespace: CompactString,
-    /// Tag descriptions within this list.
-    pub tags: HashMap<Uuid, Tag>,
+    /// Attribute layout preferred by the tag list, unless overridden by the generator.
+    pub attribute_layout: Option<crate::generator::AttributeLayout>,
+    /// Description of the document format as a whole.
+    pub overview: Overview,
+    /// Named sets of attributes included by tags, in source order and indexed by [`AttributeOrigin::Group`].
+    pub attribute_groups: Vec<AttributeGroup>,
+    /// Attributes every tag accepts, which are also merged into [`Tag::attributes`] where not shadowed.
+    pub global_attributes: SmallVec<[Attribute; 4]>,
+    /// Tag descriptions within this list, in source order and indexed by [`TagId`].
+    pub tags: Vec<Tag>,
     /// Mapping between tag names and internal ids.
-    pub names: HashMap<CompactString, Uuid>,
-    /// Lookup for child -> parent tag relations.
-    pub parents: HashMap<Uuid, SmallVec<[Uuid; 4]>>,
+    pub names: HashMap<CompactString, TagId>,
+    /// Lookup for child -> parent tag relations, parents in source order.
+    pub parents: BTreeMap<TagId, SmallVec<[TagId; 4]>>,
+    /// Tags documents start with, as declared by the tag list
+    /// or else tags without possible parents, in source order.
+    pub roots: SmallVec<[TagId; 4]>,
+    /// Groups of tags which may be nested within each other, i.e. strongly connected
+    /// components with a cycle, each in source order and ordered by their first tag.
+    pub cycles: Vec<SmallVec<[TagId; 4]>>,
+}
+
+impl TagList {
+    /// Iterate over all tags in source order.
+    pub fn iter(&self) -> impl Iterator<Item = &Tag> {
+        self.tags.iter()
+    }
+
+    /// Get the tag with the internal `id`, if it belongs to this list.
+    pub fn get(&self, id: TagId) -> Option<&Tag> {
+        self.tags.get(id.index())
+    }
+}
+
+impl ops::Index<TagId> for TagList {
+    type Output = Tag;
+
+    fn index(&self, id: TagId) -> &Tag {
+        &self.tags[id.index()]
+    }
+}
+
+/// Document-level description of a tag list, introducing the generated reference.
+#[derive(Debug, Default)]
+pub struct Overview {
+    /// Name of the document format.
+    pub title: Option<String>,
+    /// Free-form description of the document format.
+    pub description: Option<String>,
+    /// File extensions of documents, without the leading dot.
+    pub extensions: SmallVec<[CompactString; 2]>,
+    /// Media (MIME) type of documents.
+    pub media_type: Option<CompactString>,
+}
+
+impl Overview {
+    /// Check if the tag list describes nothing on the document level.
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none() && self.description.is_none() && self.extensions.is_empty() && self.media_type.is_none()
+    }
+}
+
+/// Named set of attributes, which tags include rather than repeating them.
+#[derive(Debug, Default)]
+pub struct AttributeGroup {
+    /// Name tags refer to the group by.
+    pub name: CompactString,
+    /// Optional description of what the attributes have in common.
+    pub description: Option<String>,
+    /// The attributes of this group, in source order.
+    pub attributes: SmallVec<[Attribute; 4]>,
+}
+
+/// Internal identifier of a tag: its position in the source tag list,
+/// which keeps everything keyed by it reproducible between runs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TagId(u32);
+
+impl TagId {
+    /// Position of the tag within [`TagList::tags`].
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+
+    /// Identifier of the tag at `index` within [`TagList::tags`], for tests outside of the loader.
+    #[cfg(test)]
+    pub(crate) fn from_index(index: usize) -> Self {
+        TagId(index as u32)
+    }
+}
+
+impl fmt::Display for TagId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!("#{}", self.0))
+    }
 }
 
 /// Description of a tag.
 #[derive(Debug, Default)]
 pub struct Tag {
     /// Internal identifier of this tag.
-    pub id: Uuid,
+    pub id: TagId,
     /// Public tag name.
     pub name: CompactString,
     /// Mandatory description.
     pub description: String,
+    /// Optional free-form group used by the generated index.
+    pub category: Option<CompactString>,
     /// The attributes this tag may have.
     pub attributes: SmallVec<[Attribute; 4]>,
     /// The child tags this tag may contain.
@@ -44,18 +137,18 @@ pub struct Tag {
     pub value: Option<String>,
     /// An abstract XML example code demonstrating this tag.
     pub example: Option<String>,
-    /// Order of the tag definition in its source file.
-    index_internal: i32,
-}
-impl Tag {
-    #[inline]
-    pub fn index(&self) -> i32 {
-        self.index_internal
-    }
+    /// Flag showing whether the tag should no longer be used.
+    pub is_deprecated: bool,
+    /// Flag showing whether the tag may be nested within itself, see [`TagList::cycles`].
+    pub is_recursive: bool,
+    /// Flag showing whether the tag only serves as a base for others, and can't appear in documents.
+    pub is_abstract: bool,
+    /// Tags this one inherits attributes and children from, in order of precedence (later wins).
+    pub bases: SmallVec<[TagId; 2]>,
 }
 
 /// Description of an allowed (or expected) tag attribute.
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct Attribute {
     /// Attribute name.
     pub name: CompactString,
@@ -69,6 +162,24 @@ pub struct Attribute {
     pub expected_value: Option<CompactString>,
     /// The default value this tag would have if it `is_optional`.
     pub default_value: Option<CompactString>,
+    /// Flag showing whether the attribute should no longer be used.
+    pub is_deprecated: bool,
+    /// Where the attribute was declared.
+    pub origin: AttributeOrigin,
+}
+
+/// Where an attribute of a tag was declared.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AttributeOrigin {
+    /// By the tag itself.
+    #[default]
+    Declared,
+    /// By a (possibly indirect) base tag.
+    Inherited { base: TagId },
+    /// By an attribute group the tag includes, indexed into [`TagList::attribute_groups`].
+    Group { group: usize },
+    /// By the tag list for every tag, see [`TagList::global_attributes`].
+    Global,
 }
 
 /// Description of a tag (subject) which may be used within another tag (parent).
@@ -84,7 +195,9 @@ pub struct Child {
 
 #[derive(Debug)]
 pub enum ChildInternal {
-    Resolved { id: Uuid },
+    Resolved { id: TagId },
+    /// Reference to an abstract tag, which can't appear in documents itself.
+    Abstract { id: TagId },
     Unresolved { name: CompactString },
 }
 impl Default for ChildInternal {
@@ -97,7 +210,13 @@ impl Default for ChildInternal {
 
 /// Encapsulation of [`super::model`] loading logic.
 pub mod loader {
+    use std::collections::hash_map::Entry;
+    use std::fs;
+    use std::io;
+    use std::path::Path;
     use smallvec::smallvec;
+    use crate::names::{self, NameError};
+    use crate::schema::{self, FileRoot, SourceFormat, UnknownKey};
     use super::*;
 
 
@@ -109,16 +228,500 @@ pub mod loader {
         pub warnings: SmallVec<[String; 4]>,
     }
 
-    /// Possible fatal errors produced by [`load_from`].
+    /// Deserialized tag list file with possible warnings.
+    pub struct ReadDigest {
+        /// Tag list as written in the file.
+        pub root: FileRoot,
+        /// Non-fatal issues.
+        pub warnings: SmallVec<[String; 4]>,
+    }
+
+    /// Configuration of [`load_path_with`] and [`read_path_with`].
+    #[derive(Clone, Debug, Default)]
+    pub struct LoadOptions {
+        /// Format of the tag list file, guessed from its extension if missing.
+        pub format: Option<SourceFormat>,
+        /// Whether keys unknown to the schema are fatal rather than warnings.
+        pub deny_unknown_keys: bool,
+        /// Whether tags with colliding anchors are warnings rather than fatal.
+        pub allow_anchor_collisions: bool,
+    }
+
+    /// Possible fatal errors produced by [`load_from`] and [`load_path`].
     #[derive(Debug)]
     pub enum LoadError {
+        /// Source file couldn't be opened or read.
+        InputOutput { inner: io::Error },
+        /// Source file isn't a valid tag list.
+        Parse { format: crate::schema::SourceFormat, inner: crate::schema::FormatError },
         /// Schema version wasn't supported.
-        VersionUnsupported { found: CompactString, expected: CompactString }
+        VersionUnsupported { found: CompactString, expected: CompactString },
+        /// Source file has keys unknown to the schema, which were denied.
+        UnknownKeys { keys: Vec<UnknownKey> },
+        /// Tags or attributes have names which aren't legal in XML.
+        InvalidNames { names: Vec<InvalidName> },
+        /// Several tags have the same name, so references to them would be ambiguous.
+        DuplicateTags { names: Vec<CompactString> },
+        /// Tags have names which produce the same anchor, which weren't allowed.
+        AnchorCollisions { collisions: Vec<AnchorCollision> },
+        /// Tags require each other as children, so no finite document can contain them.
+        RequiredCycles { cycles: Vec<SmallVec<[CompactString; 4]>> },
+        /// Tags extend each other, so their inheritance can't be flattened.
+        InheritanceCycle { tags: SmallVec<[CompactString; 4]> },
+    }
+
+    /// Tag or attribute name which isn't a legal XML name.
+    #[derive(Clone, Debug)]
+    pub struct InvalidName {
+        /// Tag with the invalid name, or whatever declares the attribute with it.
+        pub owner: NameOwner,
+        /// Attribute with the invalid name, if it isn't the tag itself.
+        pub attribute: Option<CompactString>,
+        /// Why the name isn't legal.
+        pub reason: NameError,
+    }
+
+    /// Declaration an [`InvalidName`] belongs to.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub enum NameOwner {
+        Tag { name: CompactString },
+        AttributeGroup { name: CompactString },
+        Global,
+        /// The namespace prefix of the tag list itself.
+        Namespace { prefix: CompactString },
+    }
+
+    /// Tags whose headings would get the same link target, e.g. `Item` and `item`.
+    #[derive(Clone, Debug)]
+    pub struct AnchorCollision {
+        /// The shared anchor.
+        pub anchor: String,
+        /// Names of the colliding tags, in source order.
+        pub tags: SmallVec<[CompactString; 2]>,
+    }
+
+    impl fmt::Display for AnchorCollision {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let quoted = self.tags.iter().map(|name| format!("'{}'", name)).collect::<Vec<_>>();
+            f.write_fmt(format_args!("tags {} share anchor '{}'", quoted.join(", "), self.anchor))
+        }
+    }
+
+    impl fmt::Display for InvalidName {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match (&self.owner, &self.attribute) {
+                (NameOwner::Tag { name }, Some(attribute)) =>
+                    f.write_fmt(format_args!("attribute '{}' of tag '{}': {}", attribute, name, self.reason)),
+                (NameOwner::Tag { name }, None) =>
+                    f.write_fmt(format_args!("tag '{}': {}", name, self.reason)),
+                (NameOwner::AttributeGroup { name }, Some(attribute)) =>
+                    f.write_fmt(format_args!("attribute '{}' of attribute group '{}': {}", attribute, name, self.reason)),
+                (NameOwner::AttributeGroup { name }, None) =>
+                    f.write_fmt(format_args!("attribute group '{}': {}", name, self.reason)),
+                (NameOwner::Global, Some(attribute)) =>
+                    f.write_fmt(format_args!("global attribute '{}': {}", attribute, self.reason)),
+                (NameOwner::Global, None) =>
+                    f.write_fmt(format_args!("global attributes: {}", self.reason)),
+                (NameOwner::Namespace { prefix }, _) =>
+                    f.write_fmt(format_args!("namespace prefix '{}': {}", prefix, self.reason)),
+            }
+        }
+    }
+
+    impl fmt::Display for LoadError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                LoadError::InputOutput { inner } =>
+                    f.write_fmt(format_args!("failed to read source file: {}", inner)),
+                LoadError::Parse { format, inner } => match inner.location() {
+                    Some((line, column)) =>
+                        f.write_fmt(format_args!("failed to parse {} tag list at line {}, column {}: {}", format, line, column, inner.message())),
+                    None =>
+                        f.write_fmt(format_args!("failed to parse {} tag list: {}", format, inner)),
+                },
+                LoadError::VersionUnsupported { found, expected } =>
+                    f.write_fmt(format_args!("unsupported schema version '{}', expected '{}'", found, expected)),
+                LoadError::UnknownKeys { keys } => {
+                    f.write_str("tag list has unknown keys: ")?;
+                    for (index, key) in keys.iter().enumerate() {
+                        if index > 0 {
+                            f.write_str("; ")?;
+                        }
+                        fmt::Display::fmt(key, f)?;
+                    }
+                    Ok(())
+                },
+                LoadError::InvalidNames { names } => {
+                    f.write_str("tag list has invalid names: ")?;
+                    for (index, name) in names.iter().enumerate() {
+                        if index > 0 {
+                            f.write_str("; ")?;
+                        }
+                        fmt::Display::fmt(name, f)?;
+                    }
+                    Ok(())
+                },
+                LoadError::DuplicateTags { names } =>
+                    f.write_fmt(format_args!("tag list has duplicate tags: {}", names.join(", "))),
+                LoadError::AnchorCollisions { collisions } => {
+                    f.write_str("tag list has anchor collisions: ")?;
+                    for (index, collision) in collisions.iter().enumerate() {
+                        if index > 0 {
+                            f.write_str("; ")?;
+                        }
+                        fmt::Display::fmt(collision, f)?;
+                    }
+                    Ok(())
+                },
+                LoadError::InheritanceCycle { tags } =>
+                    f.write_fmt(format_args!("tag list has an inheritance cycle: {}", tags.join(" -> "))),
+                LoadError::RequiredCycles { cycles } => {
+                    f.write_str("tag list has cycles of required children: ")?;
+                    for (index, cycle) in cycles.iter().enumerate() {
+                        if index > 0 {
+                            f.write_str("; ")?;
+                        }
+                        f.write_str(&cycle.join(", "))?;
+                    }
+                    Ok(())
+                },
+            }
+        }
+    }
+
+    impl std::error::Error for LoadError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            match self {
+                LoadError::InputOutput { inner } => Some(inner),
+                LoadError::Parse { inner, .. } => Some(inner),
+                LoadError::VersionUnsupported { .. } | LoadError::UnknownKeys { .. }
+                | LoadError::InvalidNames { .. } | LoadError::DuplicateTags { .. } | LoadError::AnchorCollisions { .. }
+                | LoadError::RequiredCycles { .. } | LoadError::InheritanceCycle { .. } => None,
+            }
+        }
+    }
+
+
+    /// Likely targets of an unresolved child reference.
+    #[derive(Clone, Debug)]
+    pub struct ReferenceSuggestion {
+        /// The unresolved reference.
+        pub reference: CompactString,
+        /// Existing tag names it likely meant, best first.
+        pub candidates: SmallVec<[CompactString; 4]>,
+    }
+
+    impl ReferenceSuggestion {
+        /// The only candidate, if the reference can be fixed without guessing.
+        pub fn unambiguous(&self) -> Option<&str> {
+            match self.candidates.as_slice() {
+                [only] => Some(only.as_str()),
+                _ => None,
+            }
+        }
+    }
+
+    impl fmt::Display for ReferenceSuggestion {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self.candidates.as_slice() {
+                [] => Ok(()),
+                [only] => f.write_fmt(format_args!("did you mean '{}'?", only)),
+                many => {
+                    let quoted = many.iter().map(|name| format!("'{}'", name)).collect::<Vec<_>>();
+                    f.write_fmt(format_args!("did you mean one of {}?", quoted.join(", ")))
+                },
+            }
+        }
     }
 
+    /// Suggest targets for every distinct unresolved child reference in `model`, in source order.
+    pub fn suggest_references(model: &TagList) -> Vec<ReferenceSuggestion> {
+        let tag_names = model.iter().map(|tag| tag.name.clone()).collect::<Vec<_>>();
+        let mut suggestions: Vec<ReferenceSuggestion> = vec![];
+
+        for child in model.iter().flat_map(|tag| tag.children.iter()) {
+            if let ChildInternal::Unresolved { name } = &child.reference {
+                if !suggestions.iter().any(|s| s.reference == *name) {
+                    suggestions.push(suggest_among(name, &tag_names));
+                }
+            }
+        }
+        suggestions
+    }
+
+    /// Order tags so that every tag comes after its bases, failing on the first inheritance cycle.
+    fn inheritance_order(model: &TagList) -> Result<Vec<TagId>, LoadError> {
+        fn visit(model: &TagList, id: TagId, path: &mut Vec<TagId>, done: &mut Vec<bool>, order: &mut Vec<TagId>) -> Result<(), LoadError> {
+            if done[id.index()] {
+                return Ok(());
+            }
+            if let Some(start) = path.iter().position(|ancestor| *ancestor == id) {
+                let tags = path[start..].iter().chain([&id])
+                    .map(|id| model[*id].name.clone())
+                    .collect();
+                return Err(LoadError::InheritanceCycle { tags });
+            }
+
+            path.push(id);
+            for base in &model[id].bases {
+                visit(model, *base, path, done, order)?;
+            }
+            path.pop();
+
+            done[id.index()] = true;
+            order.push(id);
+            Ok(())
+        }
+
+        let mut done = vec![false; model.tags.len()];
+        let mut order = Vec::with_capacity(model.tags.len());
+        for tag in model.iter() {
+            visit(model, tag.id, &mut vec![], &mut done, &mut order)?;
+        }
+        Ok(order)
+    }
+
+    /// Report tags which can't appear in a document starting with one of the root tags.
+    ///
+    /// Unreachable tags without parents are orphans, and any tags only reachable from orphans
+    /// are left at that. The rest descend from isolated cycles, i.e. strongly connected groups of
+    /// tags which only have each other as ancestors, and are reported with the cycle they descend from.
+    fn check_reachability(model: &TagList) -> Vec<String> {
+        let reachable_from = |starts: &mut dyn Iterator<Item = TagId>, reached: &mut Vec<bool>| {
+            let mut pending = starts.collect::<Vec<_>>();
+            while let Some(id) = pending.pop() {
+                if std::mem::replace(&mut reached[id.index()], true) {
+                    continue;
+                }
+                pending.extend(resolved_children(&model[id]));
+            }
+        };
+
+        let mut warnings = vec![];
+        // Abstract tags never appear in documents, so there's nothing to reach.
+        let mut reached = model.iter().map(|tag| tag.is_abstract).collect::<Vec<_>>();
+        reachable_from(&mut model.roots.iter().copied(), &mut reached);
+
+        let orphans = model.iter()
+            .filter(|tag| !reached[tag.id.index()] && !model.parents.contains_key(&tag.id))
+            .map(|tag| tag.id)
+            .collect::<Vec<_>>();
+        for id in &orphans {
+            warnings.push(format!("orphan tag: {} isn't a root tag and has no parents", model[*id].name));
+        }
+        reachable_from(&mut orphans.into_iter(), &mut reached);
+
+        // Every tag left has a parent which wasn't reached either, so following parents always
+        // ends up in a cycle. Report the cycles without ancestors, then what descends from them.
+        for cycle in &model.cycles {
+            let isolated = cycle.iter().all(|id| !reached[id.index()])
+                && cycle.iter()
+                    .flat_map(|id| model.parents.get(id).into_iter().flatten())
+                    .all(|parent| cycle.contains(parent));
+            if !isolated {
+                continue;
+            }
+
+            let cycle_names = cycle.iter()
+                .map(|id| model[*id].name.clone())
+                .collect::<SmallVec<[CompactString; 4]>>()
+                .join(", ");
+            warnings.push(format!("isolated cycle: {} are unreachable from root tags, with no ancestors outside of them", cycle_names));
+
+            let before = reached.clone();
+            reachable_from(&mut cycle.iter().copied(), &mut reached);
+            let descendants = model.iter()
+                .filter(|tag| reached[tag.id.index()] && !before[tag.id.index()] && !cycle.contains(&tag.id))
+                .map(|tag| tag.name.clone())
+                .collect::<SmallVec<[CompactString; 4]>>();
+            if !descendants.is_empty() {
+                warnings.push(format!("unreachable tags: {} only descend from the isolated cycle {}", descendants.join(", "), cycle_names));
+            }
+        }
+        debug_assert!(reached.iter().all(|reached| *reached), "unreachable tags outside of isolated cycles");
+        warnings
+    }
+
+    /// Find strongly connected components of the child graph which contain a cycle, considering
+    /// only children which can't be omitted if `required_only`, using Tarjan's algorithm.
+    fn find_cycles(model: &TagList, required_only: bool) -> Vec<SmallVec<[TagId; 4]>> {
+        struct State<'a> {
+            model: &'a TagList,
+            required_only: bool,
+            counter: usize,
+            index: Vec<Option<usize>>,
+            low_link: Vec<usize>,
+            stack: Vec<TagId>,
+            on_stack: Vec<bool>,
+            cycles: Vec<SmallVec<[TagId; 4]>>,
+        }
+
+        impl State<'_> {
+            fn children(&self, id: TagId) -> SmallVec<[TagId; 4]> {
+                if self.model[id].is_abstract {
+                    return SmallVec::new();
+                }
+                self.model[id].children.iter()
+                    .filter(|child| !self.required_only || !child.is_optional)
+                    .filter_map(|child| match &child.reference {
+                        ChildInternal::Resolved { id } => Some(*id),
+                        ChildInternal::Abstract { .. } | ChildInternal::Unresolved { .. } => None,
+                    })
+                    .collect()
+            }
+
+            fn visit(&mut self, id: TagId) {
+                let at = id.index();
+                self.index[at] = Some(self.counter);
+                self.low_link[at] = self.counter;
+                self.counter += 1;
+                self.stack.push(id);
+                self.on_stack[at] = true;
+
+                for child in self.children(id) {
+                    match self.index[child.index()] {
+                        None => {
+                            self.visit(child);
+                            self.low_link[at] = self.low_link[at].min(self.low_link[child.index()]);
+                        },
+                        Some(index) if self.on_stack[child.index()] => {
+                            self.low_link[at] = self.low_link[at].min(index);
+                        },
+                        Some(_) => (),
+                    }
+                }
+
+                if Some(self.low_link[at]) == self.index[at] {
+                    let mut component = SmallVec::<[TagId; 4]>::new();
+                    while let Some(member) = self.stack.pop() {
+                        self.on_stack[member.index()] = false;
+                        component.push(member);
+                        if member == id {
+                            break;
+                        }
+                    }
+                    if component.len() > 1 || self.children(id).contains(&id) {
+                        component.sort();
+                        self.cycles.push(component);
+                    }
+                }
+            }
+        }
+
+        let count = model.tags.len();
+        let mut state = State {
+            model,
+            required_only,
+            counter: 0,
+            index: vec![None; count],
+            low_link: vec![0; count],
+            stack: vec![],
+            on_stack: vec![false; count],
+            cycles: vec![],
+        };
+        for tag in model.iter() {
+            if state.index[tag.id.index()].is_none() {
+                state.visit(tag.id);
+            }
+        }
+
+        state.cycles.sort();
+        state.cycles
+    }
+
+    fn resolved_children(tag: &Tag) -> impl Iterator<Item = TagId> + '_ {
+        tag.children.iter().filter_map(|child| match &child.reference {
+            ChildInternal::Resolved { id } => Some(*id),
+            ChildInternal::Abstract { .. } | ChildInternal::Unresolved { .. } => None,
+        })
+    }
+
+    /// Group tags whose generated anchors are the same, in order of their first tag.
+    fn find_anchor_collisions(model: &TagList) -> Vec<AnchorCollision> {
+        let mut collisions: Vec<AnchorCollision> = vec![];
+        let mut positions = HashMap::<String, usize>::with_capacity(model.tags.len());
+        for tag in model.iter() {
+            let anchor = crate::generator::Anchors::base(&model.namespace, &tag.name);
+            match positions.get(&anchor) {
+                Some(position) => collisions[*position].tags.push(tag.name.clone()),
+                None => {
+                    positions.insert(anchor.clone(), collisions.len());
+                    collisions.push(AnchorCollision { anchor, tags: smallvec![tag.name.clone()] });
+                },
+            }
+        }
+        collisions.retain(|collision| collision.tags.len() > 1);
+        collisions
+    }
+
+    fn load_attribute(attr_schema: schema::Attribute, origin: AttributeOrigin) -> Attribute {
+        Attribute {
+            name: attr_schema.id,
+            short_description: attr_schema.brief.trim().into(),
+            long_description: attr_schema.description.map(|d| d.trim().into()),
+            is_optional: attr_schema.optional.unwrap_or(false),
+            expected_value: attr_schema.expected.map(|ev| ev.trim().into()),
+            default_value: attr_schema.default.map(|dv| dv.trim().into()),
+            is_deprecated: attr_schema.deprecated.unwrap_or(false),
+            origin,
+        }
+    }
+
+    fn suggest_among(reference: &CompactString, tag_names: &[CompactString]) -> ReferenceSuggestion {
+        ReferenceSuggestion {
+            reference: reference.clone(),
+            candidates: crate::suggest::similar_names(reference, tag_names.iter().map(CompactString::as_str))
+                .into_iter()
+                .map(CompactString::from)
+                .collect(),
+        }
+    }
+
+
+    /// Load a [`TagList`] model from a tag list file at `path` with default options.
+    pub fn load_path(path: &Path) -> Result<LoadDigest, LoadError> {
+        load_path_with(path, &LoadOptions::default())
+    }
+
+    /// Load a [`TagList`] model from a tag list file at `path`.
+    pub fn load_path_with(path: &Path, options: &LoadOptions) -> Result<LoadDigest, LoadError> {
+        let ReadDigest { root, mut warnings } = read_path_with(path, options)?;
+        let mut digest = load_from_with(root, options)?;
+
+        warnings.append(&mut digest.warnings);
+        digest.warnings = warnings;
+        Ok(digest)
+    }
+
+    /// Read a deserialized tag list file at `path`, checking it for unknown keys.
+    pub fn read_path_with(path: &Path, options: &LoadOptions) -> Result<ReadDigest, LoadError> {
+        let format = options.format.unwrap_or_else(|| SourceFormat::from_path(path));
+        let text = fs::read_to_string(path)
+            .map_err(|inner| LoadError::InputOutput { inner })?;
+
+        log::trace!("input file read successfully, parsing as {}", format);
+
+        let (root, unknown_keys) = format.parse(&text)
+            .map_err(|inner| LoadError::Parse { format, inner })?;
+
+        log::trace!("schema parsed successfully, {} unknown key(s)", unknown_keys.len());
+
+        if options.deny_unknown_keys && !unknown_keys.is_empty() {
+            return Err(LoadError::UnknownKeys { keys: unknown_keys });
+        }
+
+        let warnings = unknown_keys.iter().map(ToString::to_string).collect();
+        Ok(ReadDigest { root, warnings })
+    }
+
+
+    /// Load a [`TagList`] model from a deserialized `schema` instance with default options.
+    pub fn load_from(schema: FileRoot) -> Result<LoadDigest, LoadError> {
+        load_from_with(schema, &LoadOptions::default())
+    }
 
     /// Load a [`TagList`] model from a deserialized `schema` instance.
-    pub fn load_from(schema: crate::schema::FileRoot) -> Result<LoadDigest, LoadError> {
+    pub fn load_from_with(schema: FileRoot, options: &LoadOptions) -> Result<LoadDigest, LoadError> {
         let schema_version = schema.schema.version;
         log::trace!("loading schema version = {}", schema_version);
 
@@ -133,23 +736,45 @@ pub mod loader {
         let mut tl_warnings = SmallVec::new();
         let mut tl_root = TagList {
             namespace: schema.schema.namespace,
-            tags: HashMap::new(),
+            attribute_layout: None,  // <- still need to parse the layout
+            overview: Overview {
+                title: schema.schema.title.map(|t| t.trim().into()),
+                description: schema.schema.description.map(|d| d.trim().into()),
+                extensions: Default::default(),  // <- still need to normalize extensions
+                media_type: schema.schema.media_type.map(|mt| mt.trim().into()),
+            },
+            attribute_groups: Vec::new(),
+            global_attributes: SmallVec::new(),
+            tags: Vec::new(),
             names: HashMap::new(),
-            parents: HashMap::new(),
+            parents: BTreeMap::new(),
+            roots: SmallVec::new(),
+            cycles: Vec::new(),
+        };
+
+        match schema.schema.attribute_layout.as_deref().map(str::parse) {
+            Some(Ok(layout)) => tl_root.attribute_layout = Some(layout),
+            Some(Err(error)) => tl_warnings.push(format!("schema attribute layout is ignored: {}", error)),
+            None => (),
         };
 
+        for extension in schema.schema.extensions.unwrap_or_default() {
+            let extension = extension.trim().trim_start_matches('.');
+            match extension.is_empty() {
+                true => tl_warnings.push(String::from("schema has an empty file extension")),
+                false => tl_root.overview.extensions.push(extension.into()),
+            };
+        }
+        if let Some(media_type) = tl_root.overview.media_type.as_ref().filter(|mt| !mt.contains('/')) {
+            tl_warnings.push(format!("schema media type isn't a type/subtype pair: {}", media_type));
+        }
+
+        let declared_roots = schema.schema.roots;
         let tag_count = schema.tags.len();
-        log::trace!("found {} tag(s), reserving hashmaps...", tag_count);
+        log::trace!("found {} tag(s), reserving storage...", tag_count);
 
         tl_root.names.reserve(tag_count);
         tl_root.tags.reserve(tag_count);
-        tl_root.parents.reserve(tag_count);
-
-        if tl_root.namespace.is_empty() || !tl_root.namespace.is_ascii() {
-            log::debug!("schema namespace must be a non-empty ascii sequence");
-            // TODO: Consider using CompactString for tl_warnings, or a Cow<String>.
-            tl_warnings.push(String::from("schema namespace must be a non-empty ascii sequence"));
-        }
 
         // Tags are processed in multiple steps to avoid name resolution conflicts.
         //
@@ -158,69 +783,313 @@ pub mod loader {
         // Second, we pre-build a name lookup - it will not be affected by child vectors.
         // Third, we process the temporary vector by mapping child tags into their parents.
 
-        let mut children_temp = HashMap::new();
+        let mut children_temp = Vec::with_capacity(tag_count);
+        let mut bases_temp = Vec::with_capacity(tag_count);
+        let mut invalid_names = vec![];
+
+        // The namespace prefixes every tag name, so it must be a legal name without colons too.
+        if let Err(reason) = names::check_local_name(&tl_root.namespace) {
+            let owner = NameOwner::Namespace { prefix: tl_root.namespace.clone() };
+            invalid_names.push(InvalidName { owner, attribute: None, reason });
+        } else if names::is_reserved(&tl_root.namespace) {
+            tl_warnings.push(format!("schema namespace prefix '{}' starts with 'xml', which is reserved", tl_root.namespace));
+        }
+
+        log::trace!("processing attribute groups...");
+
+        let mut group_ids = HashMap::<CompactString, usize>::new();
+        for group_schema in schema.attribute_groups.unwrap_or_default() {
+            if group_ids.contains_key(&group_schema.id) {
+                tl_warnings.push(format!("duplicate attribute group {}, only the first one is used", group_schema.id));
+                continue;
+            }
+
+            let index = tl_root.attribute_groups.len();
+            let group = AttributeGroup {
+                name: group_schema.id,
+                description: group_schema.description.map(|d| d.trim().into()),
+                attributes: group_schema.attributes
+                    .into_iter()
+                    .map(|attr_schema| load_attribute(attr_schema, AttributeOrigin::Group { group: index }))
+                    .collect(),
+            };
+            for attr in &group.attributes {
+                if let Err(reason) = names::check_qualified_name(&attr.name) {
+                    let owner = NameOwner::AttributeGroup { name: group.name.clone() };
+                    invalid_names.push(InvalidName { owner, attribute: Some(attr.name.clone()), reason });
+                } else if !attr.name.contains(':') && names::is_reserved(&attr.name) {
+                    tl_warnings.push(format!("attribute name '{}' of attribute group '{}' starts with 'xml', which is reserved", attr.name, group.name));
+                }
+            }
+
+            group_ids.insert(group.name.clone(), index);
+            tl_root.attribute_groups.push(group);
+        }
+
+        tl_root.global_attributes = schema.global_attributes
+            .unwrap_or_default()
+            .into_iter()
+            .map(|attr_schema| load_attribute(attr_schema, AttributeOrigin::Global))
+            .collect();
+        for attr in &tl_root.global_attributes {
+            if let Err(reason) = names::check_qualified_name(&attr.name) {
+                invalid_names.push(InvalidName { owner: NameOwner::Global, attribute: Some(attr.name.clone()), reason });
+            } else if !attr.name.contains(':') && names::is_reserved(&attr.name) {
+                tl_warnings.push(format!("global attribute name '{}' starts with 'xml', which is reserved", attr.name));
+            }
+        }
+        for group in &tl_root.attribute_groups {
+            for attr in group.attributes.iter().filter(|attr| tl_root.global_attributes.iter().any(|g| g.name == attr.name)) {
+                tl_warnings.push(format!("attribute {} of attribute group {} shadows the global one", attr.name, group.name));
+            }
+        }
+
+        let group_names = tl_root.attribute_groups.iter().map(|group| group.name.clone()).collect::<Vec<_>>();
 
         log::trace!("processing tag schemas...");
         debug_assert!(tl_root.names.is_empty());
 
-        for (index, tag_schema) in schema.tags.into_iter().enumerate() {
+        for tag_schema in schema.tags {
             let mut tag = Tag {
-                id: Uuid::new_v4(),
+                id: TagId(tl_root.tags.len() as u32),
                 name: tag_schema.id,
                 description: tag_schema.description.trim().into(),
+                category: tag_schema.category.map(|c| c.trim().into()),
                 attributes: Default::default(),  // <- still need to process attributes
                 children: Default::default(),  // <- still need to process child tags
                 value: tag_schema.value.map(|v| v.trim().into()),
                 example: tag_schema.example,
-                index_internal: index as i32 + 1,
+                is_deprecated: tag_schema.deprecated.unwrap_or(false),
+                is_recursive: false,  // <- only known once children are resolved
+                is_abstract: tag_schema.r#abstract.unwrap_or(false),
+                bases: Default::default(),  // <- still need to resolve base tags
             };
 
-            tag.attributes = tag_schema.attributes
+            let own_attributes = tag_schema.attributes
                 .unwrap_or_else(|| smallvec![])
                 .into_iter()
-                .map(|attr_schema| {
-                    Attribute {
-                        name: attr_schema.id,
-                        short_description: attr_schema.brief.trim().into(),
-                        long_description: attr_schema.description.map(|d| d.trim().into()),
-                        is_optional: attr_schema.optional.unwrap_or(false),
-                        expected_value: attr_schema.expected.map(|ev| ev.trim().into()),
-                        default_value: attr_schema.default.map(|dv| dv.trim().into()),
-                    }
-                })
-                .collect();
+                .map(|attr_schema| load_attribute(attr_schema, AttributeOrigin::Declared))
+                .collect::<SmallVec<[Attribute; 4]>>();
 
-            children_temp.insert(tag.id, tag_schema.children.unwrap_or_else(|| smallvec![]));
+            if let Err(reason) = names::check_local_name(&tag.name) {
+                invalid_names.push(InvalidName { owner: NameOwner::Tag { name: tag.name.clone() }, attribute: None, reason });
+            } else if names::is_reserved(&tag.name) {
+                tl_warnings.push(format!("tag name '{}' starts with 'xml', which is reserved", tag.name));
+            }
+            for attr in &own_attributes {
+                // Attributes may come from other namespaces, e.g. `xml:lang`, unlike tags of this list.
+                if let Err(reason) = names::check_qualified_name(&attr.name) {
+                    let owner = NameOwner::Tag { name: tag.name.clone() };
+                    invalid_names.push(InvalidName { owner, attribute: Some(attr.name.clone()), reason });
+                } else if !attr.name.contains(':') && names::is_reserved(&attr.name) {
+                    tl_warnings.push(format!("attribute name '{}' of tag '{}' starts with 'xml', which is reserved", attr.name, tag.name));
+                }
+            }
 
-            if tl_root.tags.insert(tag.id, tag).is_some() {
-                panic!("non-unique generated internal tag uuid?!");
+            // Attributes of included groups come first, in the order the groups are listed.
+            for group_name in tag_schema.attribute_groups.unwrap_or_default() {
+                let Some(index) = group_ids.get(&group_name) else {
+                    let suggestion = suggest_among(&group_name, &group_names);
+                    match suggestion.candidates.is_empty() {
+                        true => tl_warnings.push(format!("unresolved attribute group: {}->{}", tag.name, group_name)),
+                        false => tl_warnings.push(format!("unresolved attribute group: {}->{}, {}", tag.name, group_name, suggestion)),
+                    };
+                    continue;
+                };
+                for group_attr in &tl_root.attribute_groups[*index].attributes {
+                    match tag.attributes.iter_mut().find(|attr| attr.name == group_attr.name) {
+                        Some(attr) if attr.origin == group_attr.origin => (),
+                        Some(attr) => {
+                            tl_warnings.push(format!("tag {} includes attribute {} from several attribute groups, using the one from {}",
+                                tag.name, group_attr.name, group_name));
+                            *attr = group_attr.clone();
+                        },
+                        None => tag.attributes.push(group_attr.clone()),
+                    }
+                }
             }
+            for own_attr in own_attributes {
+                match tag.attributes.iter_mut().find(|attr| attr.name == own_attr.name) {
+                    Some(attr) => {
+                        if let AttributeOrigin::Group { group } = attr.origin {
+                            tl_warnings.push(format!("attribute {} of tag {} overrides the one from attribute group {}",
+                                own_attr.name, tag.name, tl_root.attribute_groups[group].name));
+                        }
+                        *attr = own_attr;
+                    },
+                    None => tag.attributes.push(own_attr),
+                }
+            }
+
+            children_temp.push(tag_schema.children.unwrap_or_else(|| smallvec![])
+                .into_iter()
+                .map(|child| (child, None))
+                .collect::<SmallVec<[(schema::Child, Option<TagId>); 4]>>());
+            bases_temp.push(tag_schema.extend.unwrap_or_default());
+            tl_root.tags.push(tag);
+        }
+
+        if !invalid_names.is_empty() {
+            log::trace!("found {} invalid name(s)", invalid_names.len());
+            return Err(LoadError::InvalidNames { names: invalid_names });
         }
 
         log::trace!("building the name mapping...");
         debug_assert!(tl_root.names.is_empty());
 
-        for (uuid, tag) in &tl_root.tags {
-            if tl_root.names.insert(tag.name.clone(), *uuid).is_some() {
-                panic!("non-unique name -> uuid mapping?!");
+        let mut duplicate_tags = vec![];
+        for tag in &tl_root.tags {
+            match tl_root.names.entry(tag.name.clone()) {
+                Entry::Occupied(_) if duplicate_tags.contains(&tag.name) => (),
+                Entry::Occupied(_) => duplicate_tags.push(tag.name.clone()),
+                Entry::Vacant(entry) => { entry.insert(tag.id); },
+            };
+        }
+        if !duplicate_tags.is_empty() {
+            log::trace!("found {} duplicate tag(s)", duplicate_tags.len());
+            return Err(LoadError::DuplicateTags { names: duplicate_tags });
+        }
+
+        // Only tags with different names are left to collide, which numbering can tell apart.
+        let collisions = find_anchor_collisions(&tl_root);
+        if !collisions.is_empty() {
+            if !options.allow_anchor_collisions {
+                log::trace!("found {} anchor collision(s)", collisions.len());
+                return Err(LoadError::AnchorCollisions { collisions });
+            }
+            for collision in &collisions {
+                tl_warnings.push(format!("{}, numbered in source order", collision));
             }
         }
 
-        // At this point, we can use the uuid <-> name lookup
+        // At this point, we can use the id <-> name lookup
         // tables, which is needed for child processing.
 
+        let tag_names = tl_root.tags.iter().map(|tag| tag.name.clone()).collect::<Vec<_>>();
+
+        log::trace!("flattening inheritance...");
+
+        for (index, base_names) in bases_temp.iter().enumerate() {
+            for name in base_names {
+                let tag_name = &tl_root.tags[index].name;
+                match tl_root.names.get(name) {
+                    Some(id) if tl_root.tags[index].bases.contains(id) => (),
+                    Some(id) => {
+                        if !tl_root[*id].is_abstract {
+                            tl_warnings.push(format!("tag {} extends {}, which isn't abstract", tag_name, name));
+                        }
+                        tl_root.tags[index].bases.push(*id);
+                    },
+                    None => {
+                        let suggestion = suggest_among(name, &tag_names);
+                        match suggestion.candidates.is_empty() {
+                            true => tl_warnings.push(format!("unresolved base tag: {}->{}", tag_name, name)),
+                            false => tl_warnings.push(format!("unresolved base tag: {}->{}, {}", tag_name, name, suggestion)),
+                        };
+                    },
+                }
+            }
+        }
+
+        for id in inheritance_order(&tl_root)? {
+            let tag = &tl_root[id];
+            let mut attributes = SmallVec::<[Attribute; 4]>::new();
+            let mut children = SmallVec::<[(schema::Child, Option<TagId>); 4]>::new();
+
+            for base_id in &tag.bases {
+                for base_attr in &tl_root[*base_id].attributes {
+                    let origin = match base_attr.origin {
+                        AttributeOrigin::Declared | AttributeOrigin::Group { .. } => AttributeOrigin::Inherited { base: *base_id },
+                        inherited => inherited,
+                    };
+                    let inherited = Attribute { origin, ..base_attr.clone() };
+                    match attributes.iter_mut().find(|attr| attr.name == inherited.name) {
+                        Some(attr) if attr.origin == inherited.origin => (),
+                        Some(attr) => {
+                            tl_warnings.push(format!("tag {} inherits attribute {} from several bases, using the one from {}",
+                                tag.name, inherited.name, tl_root[*base_id].name));
+                            *attr = inherited;
+                        },
+                        None => attributes.push(inherited),
+                    }
+                }
+                for (base_child, origin) in &children_temp[base_id.index()] {
+                    let inherited = (base_child.clone(), Some(origin.unwrap_or(*base_id)));
+                    match children.iter_mut().find(|(child, _)| child.r#ref == base_child.r#ref) {
+                        Some((_, origin)) if *origin == inherited.1 => (),
+                        Some(child) => *child = inherited,
+                        None => children.push(inherited),
+                    }
+                }
+            }
+
+            if attributes.is_empty() && children.is_empty() {
+                continue;
+            }
+            for attr in &tag.attributes {
+                match attributes.iter_mut().find(|inherited| inherited.name == attr.name) {
+                    Some(inherited) => *inherited = attr.clone(),
+                    None => attributes.push(attr.clone()),
+                }
+            }
+            for (own_child, _) in &children_temp[id.index()] {
+                match children.iter_mut().find(|(child, _)| child.r#ref == own_child.r#ref) {
+                    Some(inherited) => *inherited = (own_child.clone(), None),
+                    None => children.push((own_child.clone(), None)),
+                }
+            }
+
+            tl_root.tags[id.index()].attributes = attributes;
+            children_temp[id.index()] = children;
+        }
+
+        if !tl_root.global_attributes.is_empty() {
+            log::trace!("merging global attributes...");
+
+            for tag in &mut tl_root.tags {
+                for global in &tl_root.global_attributes {
+                    match tag.attributes.iter().find(|attr| attr.name == global.name) {
+                        // Inherited and group attributes were already reported with their declaration.
+                        Some(attr) if attr.origin == AttributeOrigin::Declared => {
+                            tl_warnings.push(format!("attribute {} of tag {} shadows the global one", attr.name, tag.name));
+                        },
+                        Some(_) => (),
+                        None => tag.attributes.push(global.clone()),
+                    }
+                }
+            }
+        }
+
         log::trace!("processing child <-> parent relations...");
 
-        for (parent_uuid, child_schemas) in &children_temp {
-            let parent_model = tl_root.tags.get_mut(parent_uuid)
-                .expect("failed to resolve an internal parent reference");
+        let abstract_tags = tl_root.tags.iter().map(|tag| tag.is_abstract).collect::<Vec<_>>();
+
+        for (parent_index, child_schemas) in children_temp.iter().enumerate() {
+            let parent_model = &mut tl_root.tags[parent_index];
+            let parent_id = parent_model.id;
             debug_assert!(parent_model.children.is_empty());
 
-            for child_schema in child_schemas {
+            for (child_schema, inherited_from) in child_schemas {
                 // TODO: Add a warning for invalid child names.
                 let reference = match tl_root.names.get(&child_schema.r#ref) {
-                    Some(child_uuid) => ChildInternal::Resolved { id: *child_uuid },
-                    None => ChildInternal::Unresolved { name: child_schema.r#ref.clone() },
+                    Some(child_id) if abstract_tags[child_id.index()] => {
+                        if inherited_from.is_none() {
+                            tl_warnings.push(format!("child reference to abstract tag: {}->{}", parent_model.name, child_schema.r#ref));
+                        }
+                        ChildInternal::Abstract { id: *child_id }
+                    },
+                    Some(child_id) => ChildInternal::Resolved { id: *child_id },
+                    None => {
+                        // Inherited children were already reported with the base tag.
+                        if inherited_from.is_none() {
+                            let suggestion = suggest_among(&child_schema.r#ref, &tag_names);
+                            match suggestion.candidates.is_empty() {
+                                true => tl_warnings.push(format!("unresolved child reference: {}->{}", parent_model.name, child_schema.r#ref)),
+                                false => tl_warnings.push(format!("unresolved child reference: {}->{}, {}", parent_model.name, child_schema.r#ref, suggestion)),
+                            };
+                        }
+                        ChildInternal::Unresolved { name: child_schema.r#ref.clone() }
+                    },
                 };
                 let child = Child {
                     reference,
@@ -228,42 +1097,325 @@ pub mod loader {
                     is_repeatable: child_schema.multiple.unwrap_or(false),
                 };
 
-                if let ChildInternal::Unresolved { name } = &child.reference {
-                    tl_warnings.push(format!("unresolved child reference: {}->{}", parent_model.name, name));
-                }
-
+                // Abstract tags can't contain anything themselves, only their descendants can.
                 if let ChildInternal::Resolved { id } = &child.reference {
-                    if !tl_root.parents.contains_key(id) {
-                        tl_root.parents.insert(*id, smallvec![]);
+                    if !parent_model.is_abstract {
+                        tl_root.parents.entry(*id).or_default().push(parent_id);
                     }
-                    tl_root.parents.get_mut(id).unwrap().push(*parent_uuid);
                 }
 
                 parent_model.children.push(child);
             }
         }
 
-        log::trace!("discovering root tags...");
+        log::trace!("looking for cycles...");
 
-        let root_pairs = tl_root.tags.values()
-            .map(|tag| (tag.id, tag.name.clone()))
-            .filter(|(id, _)| !tl_root.parents.contains_key(id))
-            .collect::<SmallVec<[(Uuid, CompactString); 4]>>();
+        let required_cycles = find_cycles(&tl_root, true);
+        if !required_cycles.is_empty() {
+            let cycles = required_cycles.into_iter()
+                .map(|cycle| cycle.into_iter().map(|id| tl_root[id].name.clone()).collect())
+                .collect();
+            return Err(LoadError::RequiredCycles { cycles });
+        }
 
-        match root_pairs.len() {
-            1 => (),
-            0 => {
-                tl_warnings.push(String::from("schema has no root tags, likely self-referential?"))
-            },
-            c => {
-                let names_list = root_pairs.into_iter()
-                    .map(|(_, name)| name)
-                    .collect::<SmallVec<[CompactString; 4]>>()
-                    .join(", ");
-                tl_warnings.push(format!("schema has more than one root tag ({}): {}", c, names_list))
-            },
-        };
+        tl_root.cycles = find_cycles(&tl_root, false);
+        for id in tl_root.cycles.iter().flatten() {
+            tl_root.tags[id.index()].is_recursive = true;
+        }
+
+        if let Some(declared_roots) = declared_roots {
+            log::trace!("resolving {} declared root tag(s)...", declared_roots.len());
+
+            for name in &declared_roots {
+                match tl_root.names.get(name) {
+                    Some(id) if tl_root.roots.contains(id) => (),
+                    Some(id) if tl_root[*id].is_abstract => {
+                        tl_warnings.push(format!("declared root tag {} is abstract", name));
+                    },
+                    Some(id) => tl_root.roots.push(*id),
+                    None => {
+                        let suggestion = suggest_among(name, &tag_names);
+                        match suggestion.candidates.is_empty() {
+                            true => tl_warnings.push(format!("unresolved root tag: {}", name)),
+                            false => tl_warnings.push(format!("unresolved root tag: {}, {}", name, suggestion)),
+                        };
+                    },
+                }
+            }
+            if tl_root.roots.is_empty() {
+                tl_warnings.push(String::from("schema declares no valid root tags"));
+            }
+        } else {
+            log::trace!("discovering root tags...");
+
+            let root_pairs = tl_root.iter()
+                .filter(|tag| !tag.is_abstract && !tl_root.parents.contains_key(&tag.id))
+                .map(|tag| (tag.id, tag.name.clone()))
+                .collect::<SmallVec<[(TagId, CompactString); 4]>>();
+            tl_root.roots = root_pairs.iter().map(|(id, _)| *id).collect();
+
+            match root_pairs.len() {
+                1 => (),
+                0 => {
+                    tl_warnings.push(String::from("schema has no root tags, as every tag has a parent, list them in schema.roots"))
+                },
+                c => {
+                    let names_list = root_pairs.into_iter()
+                        .map(|(_, name)| name)
+                        .collect::<SmallVec<[CompactString; 4]>>()
+                        .join(", ");
+                    tl_warnings.push(format!("schema has more than one root tag ({}): {}, list them in schema.roots if intended", c, names_list))
+                },
+            };
+        }
+
+        log::trace!("checking reachability from root tags...");
+        tl_warnings.extend(check_reachability(&tl_root));
 
         Ok(LoadDigest { model: tl_root, warnings: tl_warnings })
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::{Attribute, AttributeOrigin, Tag, TagList};
+    use super::loader::{self, LoadError, LoadOptions};
+    use crate::schema::SourceFormat;
+
+    fn load(text: &str, options: &LoadOptions) -> Result<loader::LoadDigest, LoadError> {
+        let (root, _) = SourceFormat::Yaml.parse(text).unwrap();
+        loader::load_from_with(root, options)
+    }
+
+    #[test]
+    fn duplicate_tags_are_rejected_even_with_collisions_allowed() {
+        let text = "
+schema: { version: r1, namespace: ex }
+tags:
+  - { id: a, description: First. }
+  - { id: a, description: Second. }
+  - { id: A, description: Third. }
+";
+        for allow_anchor_collisions in [false, true] {
+            let options = LoadOptions { allow_anchor_collisions, ..Default::default() };
+            match load(text, &options) {
+                Err(LoadError::DuplicateTags { names }) => assert_eq!(names, ["a"]),
+                other => panic!("expected duplicate tags, got {:?}", other.err()),
+            }
+        }
+    }
+
+    fn tag<'a>(model: &'a TagList, name: &str) -> &'a Tag {
+        model.iter().find(|tag| tag.name == name).unwrap()
+    }
+
+    fn attribute<'a>(tag: &'a Tag, name: &str) -> &'a Attribute {
+        tag.attributes.iter().find(|attr| attr.name == name).unwrap()
+    }
+
+    #[test]
+    fn nearer_declarations_override_inherited_ones() {
+        let text = "
+schema: { version: r1, namespace: ex }
+tags:
+  - id: a
+    abstract: true
+    description: A.
+    attributes:
+      - { id: x, brief: From a. }
+      - { id: y, brief: From a. }
+      - { id: z, brief: From a. }
+    children: [{ ref: leaf }]
+  - id: b
+    abstract: true
+    extend: [a]
+    description: B.
+    attributes: [{ id: y, brief: From b. }]
+    children: [{ ref: leaf, optional: true }]
+  - id: c
+    extend: [b]
+    description: C.
+    attributes: [{ id: z, brief: From c. }]
+  - { id: leaf, description: Leaf. }
+";
+        let digest = load(text, &LoadOptions::default()).unwrap();
+        let model = &digest.model;
+        let c = tag(model, "c");
+
+        let briefs = c.attributes.iter().map(|attr| (attr.name.as_str(), attr.short_description.as_str())).collect::<Vec<_>>();
+        assert_eq!(briefs, [("x", "From a."), ("y", "From b."), ("z", "From c.")]);
+        assert_eq!(attribute(c, "x").origin, AttributeOrigin::Inherited { base: tag(model, "a").id });
+        assert_eq!(attribute(c, "y").origin, AttributeOrigin::Inherited { base: tag(model, "b").id });
+        assert_eq!(attribute(c, "z").origin, AttributeOrigin::Declared);
+
+        assert_eq!(c.children.len(), 1);
+        assert!(c.children[0].is_optional);
+    }
+
+    #[test]
+    fn later_bases_take_precedence() {
+        let text = "
+schema: { version: r1, namespace: ex }
+tags:
+  - id: first
+    abstract: true
+    description: First.
+    attributes: [{ id: shared, brief: From first. }, { id: own, brief: Only in first. }]
+  - id: second
+    abstract: true
+    description: Second.
+    attributes: [{ id: shared, brief: From second. }]
+  - { id: item, extend: [first, second], description: Item. }
+";
+        let digest = load(text, &LoadOptions::default()).unwrap();
+        let model = &digest.model;
+        let item = tag(model, "item");
+
+        assert_eq!(item.bases.as_slice(), [tag(model, "first").id, tag(model, "second").id]);
+        assert_eq!(attribute(item, "shared").short_description, "From second.");
+        assert_eq!(attribute(item, "shared").origin, AttributeOrigin::Inherited { base: tag(model, "second").id });
+        assert_eq!(attribute(item, "own").origin, AttributeOrigin::Inherited { base: tag(model, "first").id });
+        assert!(digest.warnings.iter().any(|warning|
+            warning == "tag item inherits attribute shared from several bases, using the one from second"));
+    }
+
+    #[test]
+    fn inheritance_cycles_are_rejected() {
+        let text = "
+schema: { version: r1, namespace: ex }
+tags:
+  - { id: x, extend: [y], description: X. }
+  - { id: y, extend: [x], description: Y. }
+";
+        match load(text, &LoadOptions::default()) {
+            Err(error @ LoadError::InheritanceCycle { .. }) =>
+                assert_eq!(error.to_string(), "tag list has an inheritance cycle: x -> y -> x"),
+            other => panic!("expected an inheritance cycle, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn groups_and_globals_merge_before_and_after_own_attributes() {
+        let text = "
+schema: { version: r1, namespace: ex }
+attribute_groups:
+  - { id: first, attributes: [{ id: a, brief: A. }, { id: b, brief: B from first. }] }
+  - { id: second, attributes: [{ id: b, brief: B from second. }, { id: c, brief: C from second. }] }
+global_attributes:
+  - { id: d, brief: Global d. }
+  - { id: lang, brief: Language. }
+tags:
+  - id: item
+    description: Item.
+    attribute_groups: [first, second]
+    attributes: [{ id: c, brief: Own c. }, { id: d, brief: Own d. }]
+";
+        let digest = load(text, &LoadOptions::default()).unwrap();
+        let item = tag(&digest.model, "item");
+
+        let merged = item.attributes.iter()
+            .map(|attr| (attr.name.as_str(), attr.short_description.as_str(), attr.origin))
+            .collect::<Vec<_>>();
+        assert_eq!(merged, [
+            ("a", "A.", AttributeOrigin::Group { group: 0 }),
+            ("b", "B from second.", AttributeOrigin::Group { group: 1 }),
+            ("c", "Own c.", AttributeOrigin::Declared),
+            ("d", "Own d.", AttributeOrigin::Declared),
+            ("lang", "Language.", AttributeOrigin::Global),
+        ]);
+        assert_eq!(digest.warnings.as_slice(), [
+            "tag item includes attribute b from several attribute groups, using the one from second",
+            "attribute c of tag item overrides the one from attribute group second",
+            "attribute d of tag item shadows the global one",
+        ]);
+    }
+
+    #[test]
+    fn namespace_prefix_must_be_a_local_name() {
+        let with_namespace = |namespace: &str| format!("
+schema: {{ version: r1, namespace: '{}' }}
+tags:
+  - {{ id: item, description: Item. }}
+", namespace);
+
+        for (namespace, reason) in [
+            ("", "name is empty"),
+            ("1ns", "name can't start with '1'"),
+            ("my ns", "name can't contain ' ' (at character 3)"),
+            ("a:b", "local name can't contain ':' (at character 2), the namespace is added separately"),
+        ] {
+            match load(&with_namespace(namespace), &LoadOptions::default()) {
+                Err(LoadError::InvalidNames { names }) => {
+                    assert_eq!(names.len(), 1);
+                    assert_eq!(names[0].to_string(), format!("namespace prefix '{}': {}", namespace, reason));
+                },
+                other => panic!("expected an invalid namespace '{}', got {:?}", namespace, other.err()),
+            }
+        }
+
+        let digest = load(&with_namespace("ünï-code"), &LoadOptions::default()).unwrap();
+        assert!(digest.warnings.is_empty(), "{:?}", digest.warnings);
+        let digest = load(&with_namespace("xmlx"), &LoadOptions::default()).unwrap();
+        assert_eq!(digest.warnings.as_slice(), ["schema namespace prefix 'xmlx' starts with 'xml', which is reserved"]);
+    }
+
+    #[test]
+    fn isolated_cycles_only_name_their_members() {
+        let text = "
+schema: { version: r1, namespace: ex }
+tags:
+  - { id: root, description: Root. }
+  - id: a
+    description: A.
+    children: [{ ref: b, optional: true }, { ref: c, optional: true }]
+  - id: b
+    description: B.
+    children: [{ ref: a, optional: true }]
+  - { id: c, description: C. }
+";
+        let digest = load(text, &LoadOptions::default()).unwrap();
+        let warnings = digest.warnings.iter().filter(|warning| warning.contains("cycle")).collect::<Vec<_>>();
+        assert_eq!(warnings, [
+            "isolated cycle: a, b are unreachable from root tags, with no ancestors outside of them",
+            "unreachable tags: c only descend from the isolated cycle a, b",
+        ]);
+    }
+
+    #[test]
+    fn cycles_need_one_optional_edge() {
+        let cycle = |optional: bool| format!("
+schema: {{ version: r1, namespace: ex }}
+tags:
+  - {{ id: root, description: Root., children: [{{ ref: a }}] }}
+  - {{ id: a, description: A., children: [{{ ref: b }}] }}
+  - {{ id: b, description: B., children: [{{ ref: c }}] }}
+  - {{ id: c, description: C., children: [{{ ref: a, optional: {} }}] }}
+", optional);
+
+        let digest = load(&cycle(true), &LoadOptions::default()).unwrap();
+        assert_eq!(digest.model.cycles.len(), 1);
+
+        match load(&cycle(false), &LoadOptions::default()) {
+            Err(error @ LoadError::RequiredCycles { .. }) =>
+                assert_eq!(error.to_string(), "tag list has cycles of required children: a, b, c"),
+            other => panic!("expected required cycles, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn anchor_collisions_are_numbered_when_allowed() {
+        let text = "
+schema: { version: r1, namespace: ex }
+tags:
+  - { id: a, description: First. }
+  - { id: A, description: Second. }
+";
+        assert!(matches!(load(text, &LoadOptions::default()), Err(LoadError::AnchorCollisions { .. })));
+
+        let options = LoadOptions { allow_anchor_collisions: true, ..Default::default() };
+        let digest = load(text, &options).unwrap();
+        assert_eq!(digest.model.tags.len(), 2);
+        assert!(digest.warnings.iter().any(|warning| warning.contains("numbered in source order")));
+    }
+}
@@ -0,0 +1,133 @@
+use std::fmt;
+
+
+/// Reason a string isn't a legal XML local name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NameError {
+    /// Name is empty.
+    Empty,
+    /// Name starts with a character which may only appear later, e.g. a digit.
+    InvalidStart { found: char },
+    /// Name contains a character which may not appear in names at all, e.g. a space.
+    InvalidChar { found: char, position: usize },
+    /// Name contains a colon, which is reserved for namespace prefixes.
+    Colon { position: usize },
+}
+
+impl fmt::Display for NameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NameError::Empty =>
+                f.write_str("name is empty"),
+            NameError::InvalidStart { found } =>
+                f.write_fmt(format_args!("name can't start with {:?}", found)),
+            NameError::InvalidChar { found, position } =>
+                f.write_fmt(format_args!("name can't contain {:?} (at character {})", found, position + 1)),
+            NameError::Colon { position } =>
+                f.write_fmt(format_args!("local name can't contain ':' (at character {}), the namespace is added separately", position + 1)),
+        }
+    }
+}
+
+impl std::error::Error for NameError {}
+
+
+/// Check if `c` may start an XML `Name`, as per production \[4\] of XML 1.0.
+pub fn is_name_start_char(c: char) -> bool {
+    matches!(c,
+        ':' | 'A'..='Z' | '_' | 'a'..='z'
+        | '\u{C0}'..='\u{D6}' | '\u{D8}'..='\u{F6}' | '\u{F8}'..='\u{2FF}'
+        | '\u{370}'..='\u{37D}' | '\u{37F}'..='\u{1FFF}' | '\u{200C}'..='\u{200D}'
+        | '\u{2070}'..='\u{218F}' | '\u{2C00}'..='\u{2FEF}' | '\u{3001}'..='\u{D7FF}'
+        | '\u{F900}'..='\u{FDCF}' | '\u{FDF0}'..='\u{FFFD}' | '\u{10000}'..='\u{EFFFF}')
+}
+
+/// Check if `c` may appear within an XML `Name`, as per production \[4a\] of XML 1.0.
+pub fn is_name_char(c: char) -> bool {
+    is_name_start_char(c)
+        || matches!(c, '-' | '.' | '0'..='9' | '\u{B7}' | '\u{300}'..='\u{36F}' | '\u{203F}'..='\u{2040}')
+}
+
+/// Check that `name` is a legal XML `Name`, which may contain colons.
+pub fn check_name(name: &str) -> Result<(), NameError> {
+    let mut chars = name.chars().enumerate();
+    match chars.next() {
+        None => return Err(NameError::Empty),
+        Some((_, first)) if !is_name_start_char(first) => return Err(NameError::InvalidStart { found: first }),
+        Some(_) => (),
+    };
+    match chars.find(|(_, c)| !is_name_char(*c)) {
+        Some((position, found)) => Err(NameError::InvalidChar { found, position }),
+        None => Ok(()),
+    }
+}
+
+/// Check that `name` is a legal local name (`NCName` of Namespaces in XML), i.e. a `Name` without colons.
+pub fn check_local_name(name: &str) -> Result<(), NameError> {
+    check_name(name)?;
+    match name.chars().position(|c| c == ':') {
+        Some(position) => Err(NameError::Colon { position }),
+        None => Ok(()),
+    }
+}
+
+/// Check if `name` starts with `xml` in any case, which the XML specification reserves for itself.
+pub fn is_reserved(name: &str) -> bool {
+    name.get(..3).is_some_and(|prefix| prefix.eq_ignore_ascii_case("xml"))
+}
+
+/// Check that `name` is a legal qualified name (`QName` of Namespaces in XML), i.e. a local name with an optional prefix.
+pub fn check_qualified_name(name: &str) -> Result<(), NameError> {
+    match name.split_once(':') {
+        Some((prefix, local)) => {
+            check_local_name(prefix)?;
+            check_local_name(local).map_err(|error| match error {
+                NameError::InvalidChar { found, position } => NameError::InvalidChar { found, position: position + prefix.chars().count() + 1 },
+                NameError::Colon { position } => NameError::Colon { position: position + prefix.chars().count() + 1 },
+                // The local part starts right after the colon, so report an empty or invalid start as is.
+                error => error,
+            })
+        },
+        None => check_local_name(name),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_names() {
+        for name in ["item", "_item", "list-item", "item.v2", "ünïcode", "項目"] {
+            assert_eq!(check_local_name(name), Ok(()), "name {:?}", name);
+        }
+        assert_eq!(check_local_name(""), Err(NameError::Empty));
+        assert_eq!(check_local_name("2item"), Err(NameError::InvalidStart { found: '2' }));
+        assert_eq!(check_local_name("-item"), Err(NameError::InvalidStart { found: '-' }));
+        assert_eq!(check_local_name("list item"), Err(NameError::InvalidChar { found: ' ', position: 4 }));
+        assert_eq!(check_local_name("xml:lang"), Err(NameError::Colon { position: 3 }));
+    }
+
+    #[test]
+    fn qualified_names() {
+        for name in ["lang", "xml:lang", "xlink:href", "a:b-c"] {
+            assert_eq!(check_qualified_name(name), Ok(()), "name {:?}", name);
+        }
+        assert_eq!(check_qualified_name(":lang"), Err(NameError::Empty));
+        assert_eq!(check_qualified_name("xml:"), Err(NameError::Empty));
+        assert_eq!(check_qualified_name("xml:2"), Err(NameError::InvalidStart { found: '2' }));
+        // Positions stay relative to the whole name.
+        assert_eq!(check_qualified_name("xml:la ng"), Err(NameError::InvalidChar { found: ' ', position: 6 }));
+        assert_eq!(check_qualified_name("a:b:c"), Err(NameError::Colon { position: 3 }));
+    }
+
+    #[test]
+    fn reserved_names() {
+        assert!(is_reserved("xml"));
+        assert!(is_reserved("XmlThing"));
+        assert!(is_reserved("xml:lang"));
+        assert!(!is_reserved("xm"));
+        assert!(!is_reserved("html"));
+    }
+}
@@ -0,0 +1,263 @@
+use std::path::{Path, PathBuf};
+use mdbook::book::{Book, BookItem, Chapter};
+use mdbook::errors::Error;
+use mdbook::preprocess::{Preprocessor, PreprocessorContext};
+
+use crate::example;
+use crate::generator;
+use crate::graph;
+use crate::model::loader;
+
+
+/// Opening sequence of an embedding directive, e.g. `{{#xmldoc tags.yml}}`.
+const DIRECTIVE_OPEN: &str = "{{#xmldoc";
+
+/// Closing sequence of an embedding directive.
+const DIRECTIVE_CLOSE: &str = "}}";
+
+/// Heading level of generated tag sections unless configured otherwise,
+/// leaving level 1 to the chapter title.
+const DEFAULT_LEVEL: i32 = 2;
+
+
+/// The `mdBook` preprocessor, replacing `{{#xmldoc path}}` directives with generated reference.
+///
+/// Paths are relative to the chapter containing the directive, and the tag list format
+/// (YAML, JSON or TOML) is chosen by extension. The `[preprocessor.xmldoc]` table of
+/// `book.toml` may set `format`, `level`, `anchors`, `attribute-layout`, `templates`
+/// (relative to the book root), `synthesize-examples`, `example-depth`, `index`, `graph`,
+/// `order`, `abstract-tags`, `strict` (deny unknown tag list keys) and `allow-anchor-collisions`.
+///
+/// Every renderer is supported, with `html` output falling back to Markdown outside of the html renderer.
+pub struct XmlDocPreprocessor;
+
+impl Preprocessor for XmlDocPreprocessor {
+    fn name(&self) -> &str {
+        "xmldoc"
+    }
+
+    fn run(&self, ctx: &PreprocessorContext, mut book: Book) -> Result<Book, Error> {
+        let (load_options, options) = read_options(ctx)?;
+        let source_root = ctx.root.join(&ctx.config.book.src);
+
+        let mut first_error = None;
+        book.for_each_mut(|item| {
+            if first_error.is_some() {
+                return;
+            }
+            if let BookItem::Chapter(chapter) = item {
+                if let Err(error) = expand_chapter(chapter, &source_root, &load_options, &options) {
+                    first_error = Some(error);
+                }
+            }
+        });
+
+        match first_error {
+            Some(error) => Err(error),
+            None => Ok(book),
+        }
+    }
+}
+
+
+fn read_options(ctx: &PreprocessorContext) -> Result<(loader::LoadOptions, generator::GeneratorOptions), Error> {
+    let table = ctx.config.get_preprocessor("xmldoc");
+    let get = |key: &str| table.and_then(|t| t.get(key));
+
+    let format = match get("format").and_then(|v| v.as_str()) {
+        Some(name) => name.parse::<generator::OutputFormat>().map_err(Error::msg)?,
+        None => generator::OutputFormat::Markdown,
+    };
+    // Raw HTML only makes sense for the renderer which passes it through.
+    let format = match (format, ctx.renderer.as_str()) {
+        (generator::OutputFormat::Html, "html") => generator::OutputFormat::Html,
+        (generator::OutputFormat::Html, other) => {
+            log::warn!("html output isn't supported by the '{}' renderer, using markdown", other);
+            generator::OutputFormat::Markdown
+        },
+        (generator::OutputFormat::Json, _) => {
+            return Err(Error::msg("json output can't be embedded into a book"));
+        },
+        (format, _) => format,
+    };
+
+    let level = match get("level").and_then(|v| v.as_integer()) {
+        Some(level) => i32::try_from(level)
+            .map_err(|_| Error::msg(format!("invalid header level '{}'", level)))?,
+        None => DEFAULT_LEVEL,
+    };
+    let level = generator::HeaderLevel::new(level)
+        .map_err(|error| Error::msg(error.to_string()))?;
+
+    let example_depth = match get("example-depth").and_then(|v| v.as_integer()) {
+        Some(depth) => usize::try_from(depth).ok().filter(|depth| *depth > 0)
+            .ok_or_else(|| Error::msg(format!("invalid example depth '{}', expected a positive integer", depth)))?,
+        None => example::DEFAULT_DEPTH,
+    };
+
+    let anchor_style = match get("anchors").and_then(|v| v.as_str()) {
+        Some(name) => name.parse::<generator::AnchorStyle>().map_err(Error::msg)?,
+        None => generator::AnchorStyle::default(),
+    };
+
+    let attribute_layout = match get("attribute-layout").and_then(|v| v.as_str()) {
+        Some(name) => Some(name.parse::<generator::AttributeLayout>().map_err(Error::msg)?),
+        None => None,
+    };
+
+    let graph = match get("graph").and_then(|v| v.as_str()) {
+        Some(name) => Some(name.parse::<graph::GraphFormat>().map_err(Error::msg)?),
+        None => None,
+    };
+
+    let order = match get("order").and_then(|v| v.as_str()) {
+        Some(name) => name.parse::<generator::TagOrder>().map_err(Error::msg)?,
+        None => generator::TagOrder::default(),
+    };
+
+    let load_options = loader::LoadOptions {
+        format: None,
+        deny_unknown_keys: get("strict").and_then(|v| v.as_bool()).unwrap_or(false),
+        allow_anchor_collisions: get("allow-anchor-collisions").and_then(|v| v.as_bool()).unwrap_or(false),
+    };
+
+    Ok((load_options, generator::GeneratorOptions {
+        format,
+        level,
+        crlf: false,
+        synthesize_examples: get("synthesize-examples").and_then(|v| v.as_bool()).unwrap_or(false),
+        example_depth,
+        anchor_style,
+        attribute_layout,
+        templates: get("templates").and_then(|v| v.as_str()).map(|dir| ctx.root.join(dir)),
+        index: get("index").and_then(|v| v.as_bool()).unwrap_or(false),
+        graph,
+        order,
+        abstract_tags: get("abstract-tags").and_then(|v| v.as_bool()).unwrap_or(false),
+    }))
+}
+
+fn expand_chapter(chapter: &mut Chapter, source_root: &Path, load_options: &loader::LoadOptions,
+                  options: &generator::GeneratorOptions) -> Result<(), Error>
+{
+    if !chapter.content.contains(DIRECTIVE_OPEN) {
+        return Ok(());
+    }
+
+    let chapter_dir = chapter.source_path.as_deref()
+        .or(chapter.path.as_deref())
+        .and_then(Path::parent)
+        .map(|parent| source_root.join(parent))
+        .unwrap_or_else(|| source_root.to_path_buf());
+
+    let mut expanded = String::with_capacity(chapter.content.len());
+    let mut rest = chapter.content.as_str();
+
+    while let Some(start) = rest.find(DIRECTIVE_OPEN) {
+        let Some(length) = rest[start..].find(DIRECTIVE_CLOSE) else {
+            break;
+        };
+
+        // An escaped directive is kept verbatim, minus the escape.
+        if rest[..start].ends_with('\\') {
+            expanded.push_str(&rest[..start - 1]);
+            expanded.push_str(&rest[start..start + length + DIRECTIVE_CLOSE.len()]);
+            rest = &rest[start + length + DIRECTIVE_CLOSE.len()..];
+            continue;
+        }
+
+        expanded.push_str(&rest[..start]);
+
+        let argument = rest[start + DIRECTIVE_OPEN.len()..start + length].trim();
+        let path: PathBuf = chapter_dir.join(argument);
+        log::trace!("expanding xmldoc directive for {} in chapter '{}'", path.to_string_lossy(), chapter.name);
+
+        let (document, warnings) = crate::generate_path(&path, load_options, options)
+            .map_err(|error| Error::msg(format!("{} ({})", error, path.to_string_lossy())))?;
+        for warning in &warnings {
+            log::warn!("{}: {}", path.to_string_lossy(), warning);
+        }
+        expanded.push_str(&document);
+
+        rest = &rest[start + length + DIRECTIVE_CLOSE.len()..];
+    }
+
+    expanded.push_str(rest);
+    chapter.content = expanded;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use super::*;
+
+    fn context(renderer: &str, table: serde_json::Value) -> PreprocessorContext {
+        serde_json::from_value(serde_json::json!({
+            "root": "/book",
+            "config": { "book": {}, "preprocessor": { "xmldoc": table } },
+            "renderer": renderer,
+            "mdbook_version": mdbook::MDBOOK_VERSION,
+        })).unwrap()
+    }
+
+    #[test]
+    fn every_renderer_is_supported_with_a_markdown_fallback() {
+        for renderer in ["html", "markdown", "pdf"] {
+            assert!(XmlDocPreprocessor.supports_renderer(renderer));
+        }
+
+        let table = serde_json::json!({ "format": "html" });
+        let (_, options) = read_options(&context("html", table.clone())).unwrap();
+        assert_eq!(options.format, generator::OutputFormat::Html);
+        let (_, options) = read_options(&context("markdown", table)).unwrap();
+        assert_eq!(options.format, generator::OutputFormat::Markdown);
+    }
+
+    /// Expand `content` as a chapter next to a minimal tag list, returning the new content.
+    fn expand(content: &str) -> Result<String, Error> {
+        static BOOKS: AtomicUsize = AtomicUsize::new(0);
+        let book = BOOKS.fetch_add(1, Ordering::Relaxed);
+        let source_root = std::env::temp_dir().join(format!("xmldoc-book-{}-{}", std::process::id(), book));
+        std::fs::create_dir_all(source_root.join("reference")).unwrap();
+        std::fs::write(source_root.join("reference/tags.yaml"),
+            "schema: { version: r1, namespace: ex }\ntags:\n  - { id: item, description: Item. }\n").unwrap();
+
+        let mut chapter = Chapter::new("Reference", content.into(), "reference/tags.md", vec![]);
+        let (load_options, options) = read_options(&context("html", serde_json::json!({}))).unwrap();
+        let result = expand_chapter(&mut chapter, &source_root, &load_options, &options);
+        std::fs::remove_dir_all(&source_root).unwrap();
+        result.map(|_| chapter.content)
+    }
+
+    #[test]
+    fn directives_are_replaced_relative_to_the_chapter() {
+        let content = expand("# Reference\n\n{{#xmldoc tags.yaml}}\n\nThe end.\n").unwrap();
+        assert!(content.starts_with("# Reference\n\n## `ex:item`"), "{}", content);
+        assert!(content.ends_with("\nThe end.\n"), "{}", content);
+        assert!(!content.contains("{{#xmldoc"));
+
+        let error = expand("{{#xmldoc missing.yaml}}").unwrap_err();
+        assert!(error.to_string().contains("missing.yaml"), "{}", error);
+    }
+
+    #[test]
+    fn escaped_and_unterminated_directives_are_kept() {
+        assert_eq!(expand("Write \\{{#xmldoc tags.yaml}} to embed.\n").unwrap(), "Write {{#xmldoc tags.yaml}} to embed.\n");
+        assert_eq!(expand("Broken {{#xmldoc tags.yaml\n").unwrap(), "Broken {{#xmldoc tags.yaml\n");
+    }
+
+    #[test]
+    fn example_depth_must_be_positive() {
+        let (_, options) = read_options(&context("html", serde_json::json!({}))).unwrap();
+        assert_eq!(options.example_depth, example::DEFAULT_DEPTH);
+
+        let (_, options) = read_options(&context("html", serde_json::json!({ "example-depth": 1 }))).unwrap();
+        assert_eq!(options.example_depth, 1);
+
+        for depth in [0, -1] {
+            let error = read_options(&context("html", serde_json::json!({ "example-depth": depth }))).err().unwrap();
+            assert_eq!(error.to_string(), format!("invalid example depth '{}', expected a positive integer", depth));
+        }
+    }
+}
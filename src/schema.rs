@@ -1,44 +1,725 @@
+use std::fmt;
+use std::ops::Range;
+use std::path::Path;
+use std::str::FromStr;
 use compact_str::CompactString;
-use serde::{Deserialize};
+use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 
 
 /// Root structure encompassing an entire tag list file.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct FileRoot {
     pub schema: Params,
+    /// Named sets of attributes which tags may include by listing them in their `attribute_groups`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attribute_groups: Option<Vec<AttributeGroup>>,
+    /// Attributes every tag accepts, e.g. `xml:lang`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub global_attributes: Option<SmallVec<[Attribute; 4]>>,
     pub tags: Vec<Tag>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct Params {
     pub version: CompactString,
     pub namespace: CompactString,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attribute_layout: Option<CompactString>,
+    /// Tags documents may start with, if there are intentionally several or none would be discovered.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub roots: Option<SmallVec<[CompactString; 4]>>,
+    /// Name of the document format, heading the generated introduction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Overview of the document format as a whole.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// File extensions of documents, with or without the leading dot.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<SmallVec<[CompactString; 2]>>,
+    /// Media (MIME) type of documents, e.g. `application/xml`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<CompactString>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct Tag {
     pub id: CompactString,
     pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<CompactString>,
+    /// Attribute groups to include, before attributes of the tag itself.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attribute_groups: Option<SmallVec<[CompactString; 2]>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub attributes: Option<SmallVec<[Attribute; 4]>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub children: Option<SmallVec<[Child; 4]>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub example: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<bool>,
+    /// Whether the tag only serves as a base for others to `extend`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#abstract: Option<bool>,
+    /// Base tags to inherit attributes and children from, later ones taking precedence.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extend: Option<SmallVec<[CompactString; 2]>>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct AttributeGroup {
+    pub id: CompactString,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub attributes: SmallVec<[Attribute; 4]>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct Attribute {
     pub id: CompactString,
     pub brief: CompactString,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub expected: Option<CompactString>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub default: Option<CompactString>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub optional: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<bool>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct Child {
     pub r#ref: CompactString,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub optional: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub multiple: Option<bool>,
 }
+
+
+/// Serialization formats of tag list files.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SourceFormat {
+    #[default]
+    Yaml,
+    Json,
+    Toml,
+}
+
+impl FromStr for SourceFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "yaml" | "yml" => Ok(SourceFormat::Yaml),
+            "json" => Ok(SourceFormat::Json),
+            "toml" => Ok(SourceFormat::Toml),
+            other => Err(format!("unknown tag list format '{}', expected 'yaml', 'json' or 'toml'", other)),
+        }
+    }
+}
+
+impl fmt::Display for SourceFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SourceFormat::Yaml => "yaml",
+            SourceFormat::Json => "json",
+            SourceFormat::Toml => "toml",
+        })
+    }
+}
+
+impl SourceFormat {
+    /// Guess the format of a file from its extension, falling back to YAML.
+    pub fn from_path(path: &Path) -> Self {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| ext.parse().ok())
+            .unwrap_or_default()
+    }
+
+    /// Deserialize a tag list from `text` in this format,
+    /// along with any keys which don't belong to the schema.
+    pub fn parse(&self, text: &str) -> Result<(FileRoot, Vec<UnknownKey>), FormatError> {
+        let mut ignored = vec![];
+        let callback = |path: serde_ignored::Path| ignored.push(KeyPath::from(&path));
+
+        let root = match self {
+            SourceFormat::Yaml => {
+                let deserializer = serde_yaml::Deserializer::from_str(text);
+                serde_ignored::deserialize(deserializer, callback).map_err(|inner| FormatError::Yaml { inner })?
+            },
+            SourceFormat::Json => {
+                let mut deserializer = serde_json::Deserializer::from_str(text);
+                let root = serde_ignored::deserialize(&mut deserializer, callback)
+                    .map_err(|inner| FormatError::Json { inner })?;
+                deserializer.end().map_err(|inner| FormatError::Json { inner })?;
+                root
+            },
+            SourceFormat::Toml => {
+                let mut deserializer = toml::Deserializer::new(text);
+                serde_ignored::deserialize(&mut deserializer, callback).map_err(|inner| FormatError::TomlRead { inner })?
+            },
+        };
+
+        let mut claimed = vec![];
+        let unknown = ignored.into_iter()
+            .map(|path| {
+                let line = locate_key(*self, text, &path.key, &mut claimed);
+                let suggestion = path.fields()
+                    .and_then(|fields| crate::suggest::closest(&path.key, fields.iter().copied()));
+                UnknownKey { path: path.to_string(), key: path.key, suggestion, line }
+            })
+            .collect();
+
+        Ok((root, unknown))
+    }
+
+    /// Serialize a tag list into text in this format.
+    pub fn write(&self, root: &FileRoot) -> Result<String, FormatError> {
+        match self {
+            SourceFormat::Yaml => serde_yaml::to_string(root).map_err(|inner| FormatError::Yaml { inner }),
+            SourceFormat::Json => serde_json::to_string_pretty(root)
+                .map(|text| text + "\n")
+                .map_err(|inner| FormatError::Json { inner }),
+            // Going through a value lets the serializer put tables after plain keys.
+            SourceFormat::Toml => toml::Value::try_from(root)
+                .and_then(|value| toml::to_string_pretty(&value))
+                .map_err(|inner| FormatError::TomlWrite { inner }),
+        }
+    }
+
+    /// Rewrite child references in the text of a tag list, replacing every `ref` value equal to the
+    /// first element of a `fixes` pair with the second one. Formatting and comments are kept intact.
+    /// Returns the new text and the number of references replaced.
+    pub fn rewrite_references(&self, text: &str, fixes: &[(&str, &str)]) -> (String, usize) {
+        let mut output = String::with_capacity(text.len());
+        let mut count = 0;
+        let mut state = ScanState::default();
+
+        for line in text.split_inclusive('\n') {
+            let mut line = line.to_string();
+            let ranges = reference_values(*self, &line, &mut state);
+            for range in ranges.into_iter().rev() {
+                if let Some((_, to)) = fixes.iter().find(|(old, _)| *old == &line[range.clone()]) {
+                    line.replace_range(range, to);
+                    count += 1;
+                }
+            }
+            output.push_str(&line);
+        }
+        (output, count)
+    }
+}
+
+/// What earlier lines of a tag list left open, tracked by [`reference_values`].
+#[derive(Default)]
+struct ScanState {
+    /// Closing delimiter of a string which continues on the next line.
+    open_string: Option<&'static str>,
+    /// Column of the YAML key whose block scalar continues on lines indented further.
+    block_scalar: Option<usize>,
+}
+
+/// Find the byte ranges of the `ref` values in a single `line`, excluding quotes.
+///
+/// Only `ref` keys count, which the schema only has in child entries, so mentions of `ref:` within
+/// strings, comments or YAML block scalars (e.g. descriptions) are left alone.
+fn reference_values(format: SourceFormat, line: &str, state: &mut ScanState) -> Vec<Range<usize>> {
+    let content = line.trim_end_matches(['\n', '\r']);
+    let mut ranges = vec![];
+
+    if let Some(column) = state.block_scalar {
+        let indent = content.len() - content.trim_start().len();
+        if content.trim().is_empty() || indent > column {
+            return ranges;
+        }
+        state.block_scalar = None;
+    }
+
+    let key_column = content.len() - content.trim_start_matches([' ', '-']).len();
+    let separator = if format == SourceFormat::Toml { '=' } else { ':' };
+    // Keys start lines, or follow `{` and `,`, while strings may only start where a value or key does.
+    let mut key_allowed = true;
+    let mut value_start = true;
+    let mut at = 0;
+
+    while at < content.len() {
+        if let Some(close) = state.open_string {
+            match string_end(content, at, close) {
+                Some(end) => at = end,
+                None => return ranges,
+            }
+            state.open_string = None;
+            (key_allowed, value_start) = (false, false);
+            continue;
+        }
+
+        let rest = &content[at..];
+        let Some(c) = rest.chars().next() else { break };
+        if c.is_whitespace() {
+            at += c.len_utf8();
+            continue;
+        }
+        if c == '#' && format != SourceFormat::Json && content[..at].chars().next_back().is_none_or(char::is_whitespace) {
+            break;
+        }
+        if key_allowed {
+            if let Some((range, end)) = reference_key(format, content, at) {
+                ranges.push(range);
+                at = end;
+                (key_allowed, value_start) = (false, false);
+                continue;
+            }
+        }
+
+        match c {
+            '{' | ',' => (key_allowed, value_start) = (true, true),
+            '[' => (key_allowed, value_start) = (false, true),
+            '-' if format == SourceFormat::Yaml && value_start && rest[1..].chars().next().is_none_or(char::is_whitespace) => (),
+            '|' | '>' if format == SourceFormat::Yaml && value_start => {
+                state.block_scalar = Some(key_column);
+                break;
+            },
+            '"' | '\'' if key_allowed || value_start => {
+                let close = match format {
+                    SourceFormat::Toml if rest.starts_with("\"\"\"") => "\"\"\"",
+                    SourceFormat::Toml if rest.starts_with("\'\'\'") => "\'\'\'",
+                    _ if c == '"' => "\"",
+                    _ => "\'",
+                };
+                state.open_string = Some(close);
+                at += close.len();
+                continue;
+            },
+            c if c == separator => (key_allowed, value_start) = (false, true),
+            _ => (key_allowed, value_start) = (false, false),
+        }
+        at += c.len_utf8();
+    }
+    ranges
+}
+
+/// Match a `ref` key at `at` in `line`, returning the range of its value excluding quotes,
+/// and where the value ends.
+fn reference_key(format: SourceFormat, line: &str, at: usize) -> Option<(Range<usize>, usize)> {
+    let keys: &[&str] = match format {
+        SourceFormat::Yaml | SourceFormat::Toml => &["ref", "\"ref\"", "\'ref\'"],
+        SourceFormat::Json => &["\"ref\""],
+    };
+    let separator = if format == SourceFormat::Toml { '=' } else { ':' };
+
+    let rest = keys.iter().find_map(|key| line[at..].strip_prefix(key))?;
+    let rest = rest.trim_start().strip_prefix(separator)?;
+    if format == SourceFormat::Yaml && !rest.chars().next().is_none_or(char::is_whitespace) {
+        return None;
+    }
+
+    let value = rest.trim_start();
+    let start = line.len() - value.len();
+    match value.chars().next() {
+        Some(quote @ ('"' | '\'')) => {
+            let length = value[1..].find(quote)?;
+            Some((start + 1..start + 1 + length, start + length + 2))
+        },
+        Some(_) if format == SourceFormat::Yaml => {
+            let length = value.find([',', '}', ']', '#']).unwrap_or(value.len());
+            let length = value[..length].trim_end().len();
+            Some((start..start + length, start + length))
+        },
+        _ => None,
+    }
+}
+
+/// Find the end of a string closed by `close` in `line`, starting at `from` within it.
+fn string_end(line: &str, from: usize, close: &str) -> Option<usize> {
+    let mut at = from;
+    while let Some(found) = line[at..].find(close) {
+        let end = at + found;
+        let escaped = close.starts_with('"')
+            && line[from..end].chars().rev().take_while(|c| *c == '\\').count() % 2 == 1;
+        // YAML doubles single quotes within single-quoted strings.
+        let doubled = close == "\'" && line[end + 1..].starts_with('\'');
+        if !escaped && !doubled {
+            return Some(end + close.len());
+        }
+        at = end + if doubled { 2 } else { 1 };
+    }
+    None
+}
+
+/// Key of a tag list file which doesn't belong to the schema, and would be ignored.
+#[derive(Clone, Debug)]
+pub struct UnknownKey {
+    /// Path of the key, e.g. `tags[2].children[0].optinal`.
+    pub path: String,
+    /// The unknown key itself.
+    pub key: String,
+    /// The closest valid key in the same place, if it is similar enough.
+    pub suggestion: Option<&'static str>,
+    /// One-based line of the key in the source text, if it could be found.
+    pub line: Option<usize>,
+}
+
+impl fmt::Display for UnknownKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!("unknown key '{}'", self.path))?;
+        if let Some(line) = self.line {
+            f.write_fmt(format_args!(" at line {}", line))?;
+        }
+        match self.suggestion {
+            Some(suggestion) => f.write_fmt(format_args!(", did you mean '{}'?", suggestion)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Map keys and sequence indices leading to an ignored key.
+struct KeyPath {
+    segments: Vec<PathSegment>,
+    key: String,
+}
+
+enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+impl KeyPath {
+    fn from(path: &serde_ignored::Path) -> Self {
+        fn collect(path: &serde_ignored::Path, segments: &mut Vec<PathSegment>) {
+            match path {
+                serde_ignored::Path::Root => (),
+                serde_ignored::Path::Seq { parent, index } => {
+                    collect(parent, segments);
+                    segments.push(PathSegment::Index(*index));
+                },
+                serde_ignored::Path::Map { parent, key } => {
+                    collect(parent, segments);
+                    segments.push(PathSegment::Key(key.clone()));
+                },
+                serde_ignored::Path::Some { parent }
+                | serde_ignored::Path::NewtypeStruct { parent }
+                | serde_ignored::Path::NewtypeVariant { parent } => collect(parent, segments),
+            }
+        }
+
+        let mut segments = vec![];
+        collect(path, &mut segments);
+        let key = match segments.pop() {
+            Some(PathSegment::Key(key)) => key,
+            Some(PathSegment::Index(index)) => index.to_string(),
+            None => String::new(),
+        };
+        KeyPath { segments, key }
+    }
+
+    /// Valid keys of the structure containing the ignored key, if known.
+    fn fields(&self) -> Option<&'static [&'static str]> {
+        let keys = self.segments.iter()
+            .filter_map(|segment| match segment {
+                PathSegment::Key(key) => Some(key.as_str()),
+                PathSegment::Index(_) => None,
+            })
+            .collect::<Vec<_>>();
+
+        match keys.as_slice() {
+            [] => Some(field_names::<FileRoot>()),
+            ["schema"] => Some(field_names::<Params>()),
+            ["tags"] => Some(field_names::<Tag>()),
+            ["tags", "attributes"] => Some(field_names::<Attribute>()),
+            ["tags", "children"] => Some(field_names::<Child>()),
+            ["attribute_groups"] => Some(field_names::<AttributeGroup>()),
+            ["attribute_groups", "attributes"] | ["global_attributes"] => Some(field_names::<Attribute>()),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for KeyPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (position, segment) in self.segments.iter().enumerate() {
+            match segment {
+                PathSegment::Key(key) if position == 0 => f.write_str(key)?,
+                PathSegment::Key(key) => f.write_fmt(format_args!(".{}", key))?,
+                PathSegment::Index(index) => f.write_fmt(format_args!("[{}]", index))?,
+            }
+        }
+        match self.segments.is_empty() {
+            true => f.write_str(&self.key),
+            false => f.write_fmt(format_args!(".{}", self.key)),
+        }
+    }
+}
+
+/// Find the first line defining `key` in `text`, skipping lines already `claimed` by
+/// earlier keys. This is a textual search, so it may be off if the key is also valid elsewhere.
+fn locate_key(format: SourceFormat, text: &str, key: &str, claimed: &mut Vec<usize>) -> Option<usize> {
+    let defines = |line: &str| match format {
+        // Keys may also be inside flow mappings, e.g. `- { ref: a, optinal: true }`.
+        SourceFormat::Yaml => line.match_indices(key).any(|(at, _)| {
+            let bounded = line[..at].chars().next_back()
+                .is_none_or(|c| c.is_whitespace() || matches!(c, '-' | '{' | ','));
+            bounded && line[at + key.len()..].trim_start().starts_with(':')
+        }),
+        SourceFormat::Json => line.match_indices(&format!("\"{}\"", key))
+            .any(|(at, quoted)| line[at + quoted.len()..].trim_start().starts_with(':')),
+        SourceFormat::Toml => {
+            let line = line.trim_start();
+            let rest = line.strip_prefix(key)
+                .or_else(|| line.strip_prefix(&format!("\"{}\"", key)));
+            rest.is_some_and(|rest| rest.trim_start().starts_with('='))
+        },
+    };
+
+    let line = text.lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line))
+        .find(|(number, line)| !claimed.contains(number) && defines(line))
+        .map(|(number, _)| number)?;
+    claimed.push(line);
+    Some(line)
+}
+
+/// Names of the fields of a struct, as seen by its [`Deserialize`] implementation.
+fn field_names<T: for<'de> Deserialize<'de>>() -> &'static [&'static str] {
+    struct Probe<'a>(&'a mut &'static [&'static str]);
+
+    impl<'de, 'a> serde::Deserializer<'de> for Probe<'a> {
+        type Error = serde::de::value::Error;
+
+        fn deserialize_any<V: serde::de::Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+            Err(serde::de::Error::custom("not a struct"))
+        }
+
+        fn deserialize_struct<V: serde::de::Visitor<'de>>(self, _name: &'static str, fields: &'static [&'static str],
+                                                         _visitor: V) -> Result<V::Value, Self::Error> {
+            *self.0 = fields;
+            Err(serde::de::Error::custom("fields probed"))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+            option unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier ignored_any
+        }
+    }
+
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(Probe(&mut fields));
+    fields
+}
+
+/// Errors of reading or writing tag list files in a [`SourceFormat`].
+#[derive(Debug)]
+pub enum FormatError {
+    Yaml { inner: serde_yaml::Error },
+    Json { inner: serde_json::Error },
+    TomlRead { inner: toml::de::Error },
+    TomlWrite { inner: toml::ser::Error },
+}
+
+impl FormatError {
+    /// One-based line and column of the error in the source text, if known.
+    pub fn location(&self) -> Option<(usize, usize)> {
+        match self {
+            FormatError::Yaml { inner } => inner.location().map(|l| (l.line(), l.column())),
+            FormatError::Json { inner } => (inner.line() > 0).then(|| (inner.line(), inner.column())),
+            FormatError::TomlRead { inner } => inner.line_col().map(|(line, column)| (line + 1, column + 1)),
+            FormatError::TomlWrite { .. } => None,
+        }
+    }
+
+    /// Description of the error, without the location which every format writes into it.
+    pub fn message(&self) -> String {
+        let text = self.to_string();
+        match self.location() {
+            Some((line, column)) => text.replacen(&format!(" at line {} column {}", line, column), "", 1),
+            None => text,
+        }
+    }
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::Yaml { inner } => fmt::Display::fmt(inner, f),
+            FormatError::Json { inner } => fmt::Display::fmt(inner, f),
+            FormatError::TomlRead { inner } => fmt::Display::fmt(inner, f),
+            FormatError::TomlWrite { inner } => fmt::Display::fmt(inner, f),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FormatError::Yaml { inner } => Some(inner),
+            FormatError::Json { inner } => Some(inner),
+            FormatError::TomlRead { inner } => Some(inner),
+            FormatError::TomlWrite { inner } => Some(inner),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_errors_have_a_location_in_every_format() {
+        let cases = [
+            (SourceFormat::Yaml, "schema:\n  version: r1\n  namespace: [x]\ntags: []\n", (3, 14)),
+            (SourceFormat::Json, "{\"schema\": {\"version\": \"r1\",\n \"namespace\": 3}, \"tags\": []}", (2, 15)),
+            (SourceFormat::Toml, "tags = []\n[schema]\nversion = \"r1\"\nnamespace = 3\n", (4, 13)),
+        ];
+        for (format, text, location) in cases {
+            let error = format.parse(text).unwrap_err();
+            assert_eq!(error.location(), Some(location), "{} error: {}", format, error);
+            assert!(!error.message().contains(" at line "), "{} message: {}", format, error.message());
+        }
+    }
+
+    #[test]
+    fn unknown_keys_have_a_path_line_and_suggestion_in_every_format() {
+        let cases = [
+            (SourceFormat::Yaml, (10, 13), "\
+schema: { version: r1, namespace: ex }
+tags:
+  - id: a
+    description: A.
+  - id: b
+    description: B.
+    children:
+      - ref: a
+      - ref: c
+        optinal: true
+  - id: c
+    description: C.
+    colour: red
+"),
+            (SourceFormat::Json, (8, 11), r#"{
+  "schema": {"version": "r1", "namespace": "ex"},
+  "tags": [
+    {"id": "a", "description": "A."},
+    {"id": "b", "description": "B.", "children": [
+      {"ref": "a"},
+      {"ref": "c",
+       "optinal": true}
+    ]},
+    {"id": "c", "description": "C.",
+     "colour": "red"}
+  ]
+}"#),
+            (SourceFormat::Toml, (18, 23), r#"[schema]
+version = "r1"
+namespace = "ex"
+
+[[tags]]
+id = "a"
+description = "A."
+
+[[tags]]
+id = "b"
+description = "B."
+
+[[tags.children]]
+ref = "a"
+
+[[tags.children]]
+ref = "c"
+optinal = true
+
+[[tags]]
+id = "c"
+description = "C."
+colour = "red"
+"#),
+        ];
+        for (format, (optinal_line, colour_line), text) in cases {
+            let (_, mut unknown) = format.parse(text).unwrap();
+            unknown.sort_by(|a, b| a.path.cmp(&b.path));
+            let found = unknown.iter()
+                .map(|key| (key.path.as_str(), key.key.as_str(), key.line, key.suggestion))
+                .collect::<Vec<_>>();
+            assert_eq!(found, [
+                ("tags[1].children[1].optinal", "optinal", Some(optinal_line), Some("optional")),
+                ("tags[2].colour", "colour", Some(colour_line), None),
+            ], "{}", format);
+            assert_eq!(unknown[0].to_string(),
+                format!("unknown key 'tags[1].children[1].optinal' at line {}, did you mean 'optional'?", optinal_line));
+        }
+    }
+
+    #[test]
+    fn unknown_top_level_keys_have_a_bare_path() {
+        let (_, unknown) = SourceFormat::Yaml.parse("schema: { version: r1, namespace: ex, namespase: x }\ntags: []\ntag: []\n").unwrap();
+        let found = unknown.iter().map(|key| (key.path.as_str(), key.line, key.suggestion)).collect::<Vec<_>>();
+        assert_eq!(found, [("schema.namespase", Some(1), Some("namespace")), ("tag", Some(3), Some("tags"))]);
+    }
+
+    #[test]
+    fn repeated_unknown_keys_are_located_on_separate_lines() {
+        let text = "a: 1\nnested:\n  a: 2\n";
+        let mut claimed = vec![];
+        assert_eq!(locate_key(SourceFormat::Yaml, text, "a", &mut claimed), Some(1));
+        assert_eq!(locate_key(SourceFormat::Yaml, text, "a", &mut claimed), Some(3));
+        assert_eq!(locate_key(SourceFormat::Yaml, text, "a", &mut claimed), None);
+        assert_eq!(locate_key(SourceFormat::Toml, "\"a\" = 1\n", "a", &mut vec![]), Some(1));
+        assert_eq!(locate_key(SourceFormat::Json, "{\"ab\": 1,\n \"a\": 2}", "a", &mut vec![]), Some(2));
+    }
+
+    #[test]
+    fn only_child_references_are_rewritten() {
+        let cases = [
+            (SourceFormat::Yaml, r#"tags:
+  - id: list
+    description: "Lists entries, e.g. { ref: itme }."
+    example: |
+      <!-- ref: itme -->
+      - ref: itme
+    children: [{ ref: itme, multiple: true }, { ref: "itme" }]  # ref: itme
+  - id: other
+    description: 'It''s like ref: itme'
+    children:
+      - ref: itme
+        optional: true
+"#, 3),
+            (SourceFormat::Json, r#"{"tags": [
+  {"id": "list", "description": "Lists entries, e.g. \"ref\": \"itme\".",
+   "children": [{"ref": "itme"}, {"multiple": true, "ref": "itme"}]}
+]}
+"#, 2),
+            (SourceFormat::Toml, r#"[[tags]]
+id = "list"
+description = """
+ref = "itme"
+"""
+example = 'ref = "itme"'  # ref = "itme"
+children = [{ ref = "itme" }]
+
+[[tags.children]]
+ref = "itme"
+"#, 2),
+        ];
+        for (format, text, expected) in cases {
+            let (rewritten, count) = format.rewrite_references(text, &[("itme", "item")]);
+            assert_eq!(count, expected, "{}:\n{}", format, rewritten);
+            assert_eq!(rewritten.matches("item").count(), expected, "{}:\n{}", format, rewritten);
+            assert_eq!(rewritten.replace("item", "itme"), text, "{}", format);
+        }
+    }
+
+    #[test]
+    fn field_names_follow_serde_renames() {
+        assert_eq!(field_names::<Child>(), ["ref", "optional", "multiple"]);
+        assert_eq!(field_names::<FileRoot>(), ["schema", "attribute_groups", "global_attributes", "tags"]);
+    }
+}
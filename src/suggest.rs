@@ -0,0 +1,99 @@
+use smallvec::SmallVec;
+
+
+/// Find the candidate closest to `needle` by edit distance, ignoring case,
+/// as long as it is similar enough to be a likely typo.
+pub fn closest<'a>(needle: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let needle = needle.to_lowercase();
+    let threshold = (needle.chars().count() / 3).max(1);
+
+    candidates.into_iter()
+        .map(|candidate| (candidate, strsim::damerau_levenshtein(&needle, &candidate.to_lowercase())))
+        .filter(|(_, distance)| *distance <= threshold)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(candidate, _)| candidate)
+}
+
+/// Find existing `names` that a mistyped `reference` likely meant, best first.
+///
+/// Case-insensitive matches are preferred over singular / plural forms,
+/// which are in turn preferred over names within a small edit distance.
+pub fn similar_names<'a>(reference: &str, names: impl IntoIterator<Item = &'a str>) -> SmallVec<[&'a str; 4]> {
+    let reference = reference.to_lowercase();
+    let names = names.into_iter()
+        .map(|name| (name, name.to_lowercase()))
+        .collect::<Vec<_>>();
+
+    let same_case = names.iter()
+        .filter(|(_, lower)| *lower == reference)
+        .map(|(name, _)| *name)
+        .collect::<SmallVec<[&str; 4]>>();
+    if !same_case.is_empty() {
+        return same_case;
+    }
+
+    let inflected = names.iter()
+        .filter(|(_, lower)| is_plural_of(lower, &reference) || is_plural_of(&reference, lower))
+        .map(|(name, _)| *name)
+        .collect::<SmallVec<[&str; 4]>>();
+    if !inflected.is_empty() {
+        return inflected;
+    }
+
+    let threshold = (reference.chars().count() / 3).max(1);
+    let mut close = names.iter()
+        .map(|(name, lower)| (*name, strsim::damerau_levenshtein(&reference, lower)))
+        .filter(|(_, distance)| *distance <= threshold)
+        .collect::<Vec<_>>();
+    close.sort_by_key(|(_, distance)| *distance);
+    close.into_iter().take(3).map(|(name, _)| name).collect()
+}
+
+/// Check if lowercase `plural` is an English plural form of lowercase `singular`.
+fn is_plural_of(plural: &str, singular: &str) -> bool {
+    if let Some(stem) = plural.strip_suffix("ies") {
+        if singular.strip_suffix('y') == Some(stem) {
+            return true;
+        }
+    }
+    plural.strip_suffix("es") == Some(singular) || plural.strip_suffix('s') == Some(singular)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closest_respects_threshold() {
+        // One edit is allowed for names shorter than six characters, one more per three characters.
+        assert_eq!(closest("nam", ["name", "value"]), Some("name"));
+        assert_eq!(closest("nme", ["name"]), Some("name"));
+        assert_eq!(closest("nm", ["name"]), None);
+        assert_eq!(closest("atribute", ["attribute", "example"]), Some("attribute"));
+        assert_eq!(closest("atrbute", ["attribute"]), Some("attribute"));
+        assert_eq!(closest("xyz", ["attribute"]), None);
+        assert_eq!(closest("DESCRIPTON", ["description"]), Some("description"));
+    }
+
+    #[test]
+    fn similar_names_prefer_case_then_inflection() {
+        let names = ["Item", "items", "Iter"];
+        assert_eq!(similar_names("item", names).as_slice(), ["Item"]);
+        assert_eq!(similar_names("itens", names).as_slice(), ["items"]);
+        // Ties keep the order of `names`.
+        assert_eq!(similar_names("iten", names).as_slice(), ["Item", "Iter"]);
+        assert_eq!(similar_names("entries", ["entry", "entra"]).as_slice(), ["entry"]);
+        assert_eq!(similar_names("box", ["boxes", "fox"]).as_slice(), ["boxes"]);
+    }
+
+    #[test]
+    fn similar_names_respect_threshold() {
+        assert_eq!(similar_names("chld", ["child", "parent"]).as_slice(), ["child"]);
+        assert!(similar_names("xyz", ["child", "parent"]).is_empty());
+        // At most three candidates, closest first.
+        let names = ["abcd", "abce", "abcf", "abcg", "abxy"];
+        assert_eq!(similar_names("abcz", names).len(), 3);
+        assert_eq!(similar_names("abxz", names).as_slice(), ["abxy"]);
+    }
+}